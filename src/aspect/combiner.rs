@@ -5,70 +5,78 @@ use bevy_tweening::{
     lens::{TransformPositionLens, TransformScaleLens},
     Animator, EaseFunction, Tracks, Tween,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
     aspect::icon::{DEFAULT_ICON_POSITION, HIGHLIGHTED_ICON_POSITION},
     audio::PlaySound,
     player::input::PlayerInput,
+    run::reset_resource,
     GameAssets, GameState,
 };
 
 use super::{
     icon::icon_texture,
+    recipes::AspectRecipes,
     socket::{CombinerIcon, Socket},
     Aspect, AspectCombiner,
 };
 
-#[derive(Event)]
-pub struct CombinedAspect;
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct CombinedAspect {
+    pub left: Aspect,
+    pub right: Aspect,
+    pub result: Aspect,
+}
 
-#[derive(Resource, Default, Debug)]
+#[derive(Resource, Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Combiner {
     pub left_aspect: Option<Aspect>,
     pub right_aspect: Option<Aspect>,
     pub current_combination: Option<Aspect>,
     pub last_combined_aspect: Aspect,
     pub all_sockets_full: bool,
+    /// The results of all combinations in this run, in the order they were made.
+    #[serde(default)]
+    pub combined_aspects: Vec<Aspect>,
+}
+
+impl Combiner {
+    /// Take back the last combination, as if it never happened.
+    pub fn undo_last_combination(&mut self) {
+        self.combined_aspects.pop();
+        self.last_combined_aspect = self.combined_aspects.last().copied().unwrap_or_default();
+        self.current_combination = None;
+        self.left_aspect = None;
+        self.right_aspect = None;
+        self.all_sockets_full = false;
+    }
 }
 
-pub fn is_socket_combination_possible(combiner: &Res<Combiner>, socket: &Socket) -> bool {
-    let combiner_aspect = if socket.on_top {
+pub fn is_socket_combination_possible(
+    combiner: &Res<Combiner>,
+    recipes: &Res<AspectRecipes>,
+    socket: &Socket,
+) -> bool {
+    if socket.on_top {
         match combiner.right_aspect {
-            Some(r) => r,
-            None => return true,
+            Some(r) => aspect_combinations(recipes, &socket.aspect, &r) != Aspect::NotImplemented,
+            None => true,
         }
     } else {
         match combiner.left_aspect {
-            Some(r) => r,
-            None => return true,
-        }
-    };
-    aspect_combinations(&socket.aspect, &combiner_aspect) != Aspect::NotImplemented
-}
-
-pub fn aspect_combinations(left_aspect: &Aspect, right_aspect: &Aspect) -> Aspect {
-    fn match_aspects(left_aspect: &Aspect, right_aspect: &Aspect) -> Aspect {
-        match (left_aspect, right_aspect) {
-            (Aspect::Joy, Aspect::Sadness) => Aspect::Nostalgia,
-            (Aspect::Joy, Aspect::Nostalgia) => Aspect::Motivation,
-            (Aspect::Sadness, Aspect::Nostalgia) => Aspect::Melancholy,
-            (Aspect::Anger, Aspect::Fear) => Aspect::Hatred,
-            (Aspect::Anger, Aspect::Hatred) => Aspect::Vengefulness,
-            (Aspect::Joy, Aspect::Motivation) => Aspect::Elation,
-            (Aspect::Hatred, Aspect::Motivation) => Aspect::Pride,
-            (Aspect::Nostalgia, Aspect::Motivation) => Aspect::Anticipation,
-            (Aspect::Anger, Aspect::Pride) => Aspect::Envy,
-            (Aspect::Anticipation, Aspect::Elation) => Aspect::Forgiveness,
-            _ => Aspect::NotImplemented,
+            Some(l) => aspect_combinations(recipes, &l, &socket.aspect) != Aspect::NotImplemented,
+            None => true,
         }
     }
+}
 
-    let result_aspect = match_aspects(left_aspect, right_aspect);
-    if result_aspect != Aspect::NotImplemented {
-        result_aspect
-    } else {
-        match_aspects(right_aspect, left_aspect)
-    }
+pub fn aspect_combinations(
+    recipes: &AspectRecipes,
+    left_aspect: &Aspect,
+    right_aspect: &Aspect,
+) -> Aspect {
+    recipes.combine(left_aspect, right_aspect)
 }
 
 fn select_aspects(
@@ -127,6 +135,7 @@ fn select_aspects(
 fn show_combiner_icon(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    recipes: Res<AspectRecipes>,
     mut combiner: ResMut<Combiner>,
     mut q_combiner_icon: Query<(Entity, &mut Handle<Image>), With<CombinerIcon>>,
     mut visible: Local<bool>,
@@ -137,7 +146,7 @@ fn show_combiner_icon(
     };
 
     if let (Some(left_aspect), Some(right_aspect)) = (combiner.left_aspect, combiner.right_aspect) {
-        let combined_aspect = aspect_combinations(&left_aspect, &right_aspect);
+        let combined_aspect = aspect_combinations(&recipes, &left_aspect, &right_aspect);
         combiner.current_combination = Some(combined_aspect);
         *texture = icon_texture(&assets, &combined_aspect);
 
@@ -191,6 +200,7 @@ fn show_combiner_icon(
 
 fn select_combined_aspect(
     player_input: Res<PlayerInput>,
+    recipes: Res<AspectRecipes>,
     mut combiner: ResMut<Combiner>,
     q_combiner: Query<&TextureAtlas, With<AspectCombiner>>,
     mut ev_combined_aspect: EventWriter<CombinedAspect>,
@@ -214,12 +224,17 @@ fn select_combined_aspect(
             return;
         };
 
-    let combined_aspect = aspect_combinations(&left_aspect, &right_aspect);
+    let combined_aspect = aspect_combinations(&recipes, &left_aspect, &right_aspect);
 
     combiner.last_combined_aspect = combined_aspect;
+    combiner.combined_aspects.push(combined_aspect);
     combiner.left_aspect = None;
     combiner.right_aspect = None;
-    ev_combined_aspect.send(CombinedAspect);
+    ev_combined_aspect.send(CombinedAspect {
+        left: left_aspect,
+        right: right_aspect,
+        result: combined_aspect,
+    });
 }
 
 fn check_all_aspects_full(mut combiner: ResMut<Combiner>, q_sockets: Query<&Socket>) {
@@ -245,6 +260,7 @@ impl Plugin for AspectCombinerPlugin {
                 .run_if(in_state(GameState::Gaming)),
         )
         .init_resource::<Combiner>()
-        .add_event::<CombinedAspect>();
+        .add_event::<CombinedAspect>()
+        .add_systems(OnEnter(GameState::Intro), reset_resource::<Combiner>);
     }
 }
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use crate::{
+    audio::PlaySound,
+    locale::Locale,
+    player::{input::PlayerInput, Player},
+    run::reset_resource,
+    world::{Bed, LevelEntered},
+    GameAssets, GameState,
+};
+
+use super::{
+    name_text::AspectNameText,
+    socket::{set_socket_aspect, AspectIcon, Socket},
+    Aspect, Combiner,
+};
+
+/// A single combination and the sockets its result was put into.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CombinationRecord {
+    pub left: Aspect,
+    pub right: Aspect,
+    pub result: Aspect,
+    /// The result goes into one socket of each row, so there are usually two.
+    pub sockets: Vec<Entity>,
+}
+
+/// The combinations made in the current level, the last one can be undone.
+/// Sockets are level scoped, so the history starts over whenever we enter a level.
+#[derive(Resource, Default, Debug)]
+pub struct CombinationHistory {
+    records: Vec<CombinationRecord>,
+}
+
+impl CombinationHistory {
+    pub fn push(&mut self, record: CombinationRecord) {
+        self.records.push(record);
+    }
+
+    pub fn pop(&mut self) -> Option<CombinationRecord> {
+        self.records.pop()
+    }
+}
+
+fn undo_combination(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    locale: Res<Locale>,
+    player_input: Res<PlayerInput>,
+    mut combiner: ResMut<Combiner>,
+    mut history: ResMut<CombinationHistory>,
+    q_player: Query<&Player>,
+    q_beds: Query<(Entity, &Bed)>,
+    mut q_sockets: Query<(&Children, &mut TextureAtlas, &mut Socket)>,
+    mut q_icons: Query<&mut Handle<Image>, With<AspectIcon>>,
+    mut q_texts: Query<&mut Text, With<AspectNameText>>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+) {
+    if !player_input.undo_combination {
+        return;
+    }
+    // No taking back combinations in the middle of a dialogue.
+    match q_player.get_single() {
+        Ok(player) if player.can_move => {}
+        _ => return,
+    }
+    // Once the player went to bed the ending is already on its way.
+    if q_beds.iter().any(|(_, bed)| bed.used) {
+        return;
+    }
+
+    let record = match history.pop() {
+        Some(r) => r,
+        None => return,
+    };
+
+    for entity in &record.sockets {
+        if let Ok((children, mut atlas, mut socket)) = q_sockets.get_mut(*entity) {
+            // Empty sockets never get highlighted, so the name text would stay visible otherwise.
+            atlas.index = 0;
+            set_socket_aspect(
+                &assets,
+                &locale,
+                children,
+                &mut socket,
+                Aspect::NotImplemented,
+                &mut q_icons,
+                &mut q_texts,
+            );
+        }
+    }
+    combiner.undo_last_combination();
+    info!(
+        "undid combination {} + {} = {}",
+        record.left, record.right, record.result
+    );
+
+    for (entity, _) in &q_beds {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    ev_play_sound.send(PlaySound {
+        clip: assets.deselect_aspect.clone(),
+        ..default()
+    });
+}
+
+pub struct AspectHistoryPlugin;
+
+impl Plugin for AspectHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombinationHistory>()
+            .add_systems(
+                OnEnter(GameState::Intro),
+                reset_resource::<CombinationHistory>,
+            )
+            .add_systems(
+                OnEnter(GameState::Title),
+                reset_resource::<CombinationHistory>,
+            )
+            .add_systems(
+                Update,
+                (
+                    reset_resource::<CombinationHistory>.run_if(on_event::<LevelEntered>()),
+                    undo_combination,
+                )
+                    .run_if(in_state(GameState::Gaming)),
+            );
+    }
+}
@@ -1,15 +1,24 @@
 mod combiner;
+mod history;
 mod icon;
 mod name_text;
+mod recipes;
 mod socket;
+#[cfg(test)]
+mod solver;
+#[cfg(test)]
+mod test;
 
 pub use combiner::{CombinedAspect, Combiner};
-pub use socket::Socket;
+pub use icon::icon_texture;
+pub use recipes::AspectRecipes;
+pub use socket::{Socket, SocketMemory};
 
 use std::str::FromStr;
 
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter, EnumString};
 
 pub struct AspectPlugin;
@@ -21,13 +30,29 @@ impl Plugin for AspectPlugin {
             combiner::AspectCombinerPlugin,
             icon::AspectIconPlugin,
             name_text::AspectNameTextPlugin,
+            recipes::AspectRecipesPlugin,
+            history::AspectHistoryPlugin,
         ))
         .register_ldtk_entity::<AspectBundle>("AspectSocket")
         .register_ldtk_entity::<CombinerBundle>("CombinerSocket");
     }
 }
 
-#[derive(Default, Reflect, Clone, PartialEq, EnumString, Display, Debug, Copy, EnumIter)]
+#[derive(
+    Default,
+    Reflect,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumString,
+    Display,
+    Debug,
+    Copy,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
 pub enum Aspect {
     #[default]
     NotImplemented,
@@ -54,15 +79,30 @@ pub struct AspectSocketInitiater {
 }
 
 impl AspectSocketInitiater {
+    /// A socket as it would come out of the LDtk level, used to set up sockets in tests.
+    #[cfg(test)]
+    pub fn new(aspect: Aspect, on_top: bool) -> Self {
+        Self { aspect, on_top }
+    }
+
     fn from_field(entity_instance: &EntityInstance) -> Self {
         let aspect = match entity_instance.get_enum_field("aspect") {
-            Ok(r) => Aspect::from_str(r).unwrap_or_default(),
-            Err(_) => Aspect::default(),
+            Ok(r) => match Aspect::from_str(r) {
+                Ok(aspect) => aspect,
+                Err(_) => {
+                    error!("unknown aspect in socket, '{}'", r);
+                    Aspect::default()
+                }
+            },
+            Err(err) => {
+                error!("could not find field, {}", err);
+                Aspect::default()
+            }
         };
         let on_top = match entity_instance.get_bool_field("on_top") {
             Ok(r) => r.to_owned(),
             Err(err) => {
-                error!("counld not find field, {}", err);
+                error!("could not find field, {}", err);
                 false
             }
         };
@@ -85,8 +125,6 @@ struct AspectBundle {
     aspect_initiater: AspectSocketInitiater,
     #[grid_coords]
     grid_coords: GridCoords,
-    #[worldly]
-    worldly: Worldly,
 }
 
 #[derive(Default, Bundle, LdtkEntity)]
@@ -95,8 +133,6 @@ struct CombinerBundle {
     aspect_combiner: AspectCombinerInitiater,
     #[grid_coords]
     grid_coords: GridCoords,
-    #[worldly]
-    worldly: Worldly,
 }
 
 #[derive(Component, Default)]
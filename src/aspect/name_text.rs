@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{locale::Locale, GameState};
 
 use super::{
     socket::{CombinerText, Socket},
@@ -51,6 +51,7 @@ fn hide_aspect_name_texts(
 }
 
 fn show_combined_aspect_name_texts(
+    locale: Res<Locale>,
     combiner: Res<Combiner>,
     q_combiner: Query<&TextureAtlas, With<AspectCombiner>>,
     mut q_combiner_text: Query<(&mut Visibility, &mut Text), With<CombinerText>>,
@@ -63,7 +64,8 @@ fn show_combined_aspect_name_texts(
     for (mut visibility, mut text) in &mut q_combiner_text {
         if atlas.index == 1 {
             *visibility = Visibility::Inherited;
-            text.sections[0].value = combiner.current_combination.unwrap_or_default().to_string();
+            text.sections[0].value =
+                locale.aspect_name(combiner.current_combination.unwrap_or_default());
         } else {
             *visibility = Visibility::Hidden;
         }
@@ -90,6 +92,31 @@ fn hide_combined_aspect_name_texts(
     }
 }
 
+/// Socket names are only set when their aspect changes, so a new language has to rename them.
+/// The combined aspect's name gets set every frame it's shown, it only needs the new font.
+fn relocalize_aspect_name_texts(
+    locale: Res<Locale>,
+    q_sockets: Query<(&Children, &Socket)>,
+    mut q_aspect_name_texts: Query<&mut Text, With<AspectNameText>>,
+    mut q_combiner_texts: Query<&mut Text, (With<CombinerText>, Without<AspectNameText>)>,
+) {
+    for (children, socket) in &q_sockets {
+        for child in children.iter() {
+            let mut text = match q_aspect_name_texts.get_mut(*child) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+
+            text.sections[0].value = locale.aspect_name(socket.aspect);
+            text.sections[0].style.font = locale.font();
+        }
+    }
+
+    for mut text in &mut q_combiner_texts {
+        text.sections[0].style.font = locale.font();
+    }
+}
+
 pub struct AspectNameTextPlugin;
 
 impl Plugin for AspectNameTextPlugin {
@@ -103,6 +130,10 @@ impl Plugin for AspectNameTextPlugin {
                 hide_combined_aspect_name_texts,
             )
                 .run_if(in_state(GameState::Gaming)),
+        )
+        .add_systems(
+            Update,
+            relocalize_aspect_name_texts.run_if(resource_changed::<Locale>),
         );
     }
 }
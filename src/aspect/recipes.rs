@@ -0,0 +1,250 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+use crate::{GameAssets, GameState};
+
+use super::Aspect;
+
+#[derive(Deserialize)]
+struct RawRecipe {
+    left: String,
+    right: String,
+    result: String,
+    #[serde(default)]
+    ordered: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Recipe {
+    pub left: Aspect,
+    pub right: Aspect,
+    pub result: Aspect,
+    /// If true, `left` has to come from a top socket and `right` from a bottom socket.
+    pub ordered: bool,
+}
+
+#[derive(Debug)]
+pub enum RecipeError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    UnknownAspect(String),
+    DuplicatePair(Aspect, Aspect),
+    Cycle(Aspect),
+}
+
+impl fmt::Display for RecipeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecipeError::Io(err) => write!(f, "could not read recipes, {}", err),
+            RecipeError::Ron(err) => write!(f, "could not parse recipes, {}", err),
+            RecipeError::UnknownAspect(name) => write!(f, "unknown aspect in recipes, '{}'", name),
+            RecipeError::DuplicatePair(left, right) => {
+                write!(f, "the pair ({}, {}) has more than one recipe", left, right)
+            }
+            RecipeError::Cycle(aspect) => {
+                write!(f, "recipes contain a cycle that goes through {}", aspect)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecipeError {}
+
+impl From<std::io::Error> for RecipeError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for RecipeError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Ron(err)
+    }
+}
+
+/// The validated recipe graph.
+/// It's both the loaded asset and the resource that the game systems read from,
+/// the resource gets replaced whenever the asset is (re)loaded.
+#[derive(Asset, Resource, TypePath, Clone, Debug, Default)]
+pub struct AspectRecipes {
+    recipes: Vec<Recipe>,
+}
+
+impl AspectRecipes {
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, RecipeError> {
+        let raw_recipes: Vec<RawRecipe> = ron::de::from_bytes(bytes)?;
+        let mut recipes = Vec::new();
+        for raw in raw_recipes {
+            recipes.push(Recipe {
+                left: parse_aspect(&raw.left)?,
+                right: parse_aspect(&raw.right)?,
+                result: parse_aspect(&raw.result)?,
+                ordered: raw.ordered,
+            });
+        }
+        Self::new(recipes)
+    }
+
+    pub fn new(recipes: Vec<Recipe>) -> Result<Self, RecipeError> {
+        let recipes = Self { recipes };
+        recipes.validate()?;
+        Ok(recipes)
+    }
+
+    pub fn recipes(&self) -> &[Recipe] {
+        &self.recipes
+    }
+
+    /// Returns the resulting aspect, `Aspect::NotImplemented` if there is no recipe.
+    pub fn combine(&self, left_aspect: &Aspect, right_aspect: &Aspect) -> Aspect {
+        if let Some(recipe) = self
+            .recipes
+            .iter()
+            .find(|r| r.left == *left_aspect && r.right == *right_aspect)
+        {
+            return recipe.result;
+        }
+
+        self.recipes
+            .iter()
+            .find(|r| !r.ordered && r.left == *right_aspect && r.right == *left_aspect)
+            .map(|r| r.result)
+            .unwrap_or_default()
+    }
+
+    fn validate(&self) -> Result<(), RecipeError> {
+        let mut pairs = HashMap::new();
+        for recipe in &self.recipes {
+            let mut keys = vec![(recipe.left, recipe.right)];
+            if !recipe.ordered {
+                keys.push((recipe.right, recipe.left));
+            }
+            for key in keys {
+                if pairs.insert(key, recipe.result).is_some() {
+                    return Err(RecipeError::DuplicatePair(key.0, key.1));
+                }
+            }
+        }
+        self.validate_acyclic()
+    }
+
+    /// Every aspect must only be reachable from aspects that were created before it,
+    /// otherwise there would be combinations that feed back into their own ingredients.
+    fn validate_acyclic(&self) -> Result<(), RecipeError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Unvisited,
+            InProgress,
+            Done,
+        }
+
+        fn visit(
+            aspect: Aspect,
+            recipes: &[Recipe],
+            marks: &mut HashMap<Aspect, Mark>,
+        ) -> Result<(), RecipeError> {
+            match marks[&aspect] {
+                Mark::Done => return Ok(()),
+                Mark::InProgress => return Err(RecipeError::Cycle(aspect)),
+                Mark::Unvisited => {}
+            }
+            marks.insert(aspect, Mark::InProgress);
+            for recipe in recipes
+                .iter()
+                .filter(|r| r.left == aspect || r.right == aspect)
+            {
+                visit(recipe.result, recipes, marks)?;
+            }
+            marks.insert(aspect, Mark::Done);
+            Ok(())
+        }
+
+        let mut marks: HashMap<Aspect, Mark> = Aspect::iter()
+            .map(|aspect| (aspect, Mark::Unvisited))
+            .collect();
+        for aspect in Aspect::iter() {
+            visit(aspect, &self.recipes, &mut marks)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_aspect(name: &str) -> Result<Aspect, RecipeError> {
+    match Aspect::from_str(name) {
+        Ok(Aspect::NotImplemented) | Err(_) => Err(RecipeError::UnknownAspect(name.to_string())),
+        Ok(aspect) => Ok(aspect),
+    }
+}
+
+#[derive(Default)]
+struct AspectRecipesLoader;
+
+impl AssetLoader for AspectRecipesLoader {
+    type Asset = AspectRecipes;
+    type Settings = ();
+    type Error = RecipeError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        AspectRecipes::from_ron(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["recipes.ron"]
+    }
+}
+
+fn insert_recipes(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    recipe_assets: Res<Assets<AspectRecipes>>,
+) {
+    match recipe_assets.get(&assets.aspect_recipes) {
+        Some(recipes) => commands.insert_resource(recipes.clone()),
+        None => error!("aspect recipes are not loaded, no combinations will be possible"),
+    }
+}
+
+fn reload_recipes(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    recipe_assets: Res<Assets<AspectRecipes>>,
+    mut ev_asset: EventReader<AssetEvent<AspectRecipes>>,
+) {
+    for ev in ev_asset.read() {
+        if !ev.is_modified(&assets.aspect_recipes) {
+            continue;
+        }
+        if let Some(recipes) = recipe_assets.get(&assets.aspect_recipes) {
+            info!("reloaded aspect recipes");
+            commands.insert_resource(recipes.clone());
+        }
+    }
+}
+
+pub struct AspectRecipesPlugin;
+
+impl Plugin for AspectRecipesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<AspectRecipes>()
+            .init_asset_loader::<AspectRecipesLoader>()
+            .init_resource::<AspectRecipes>()
+            .add_systems(OnExit(GameState::AssetLoading), insert_recipes)
+            .add_systems(
+                Update,
+                reload_recipes.run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
@@ -1,17 +1,24 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_ecs_ldtk::GridCoords;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
+    locale::Locale,
     player::{Player, PLAYER_PIVOT},
-    world::camera::{YSort, YSortChild},
+    run::{reset_resource, RunScoped},
+    world::{
+        camera::{YSort, YSortChild},
+        CurrentLevel, LevelScoped,
+    },
     GameAssets, GameState,
 };
 
 use super::{
     combiner::{aspect_combinations, is_socket_combination_possible, CombinedAspect, Combiner},
+    history::{CombinationHistory, CombinationRecord},
     icon::{icon_texture, DEFAULT_ICON_POSITION, HIGHLIGHTED_ICON_POSITION},
     name_text::AspectNameText,
+    recipes::AspectRecipes,
     Aspect, AspectCombiner, AspectCombinerInitiater, AspectSocketInitiater,
 };
 
@@ -28,19 +35,38 @@ pub struct Socket {
     pub aspect: Aspect,
     pub on_top: bool,
 }
+/// The aspects of the sockets in all levels we have been to, by level and socket position.
+/// Sockets that get respawned when we come back to a level pick up their aspect from here.
+#[derive(Resource, Default, Clone)]
+pub struct SocketMemory(pub HashMap<(String, [i32; 2]), Aspect>);
+
+impl SocketMemory {
+    /// The memory together with the sockets of the current level.
+    /// Those might have changed this frame, so they take precedence.
+    pub fn merged<'a>(
+        &self,
+        current_level: &str,
+        sockets: impl Iterator<Item = (&'a Transform, &'a Socket)>,
+    ) -> HashMap<(String, [i32; 2]), Aspect> {
+        let mut merged = self.0.clone();
+        for (transform, socket) in sockets {
+            merged.insert(
+                (current_level.to_string(), socket_position(transform)),
+                socket.aspect,
+            );
+        }
+        merged
+    }
+}
+
 #[derive(Component)]
 pub struct CombinerIcon;
 #[derive(Component)]
 pub struct CombinerText;
 
-fn spawn_bg_text(
-    commands: &mut Commands,
-    assets: &Res<GameAssets>,
-    text: &str,
-    pos: Vec3,
-) -> Entity {
+fn spawn_bg_text(commands: &mut Commands, locale: &Locale, text: &str, pos: Vec3) -> Entity {
     let text_style = TextStyle {
-        font: assets.silver_font.clone(),
+        font: locale.font(),
         font_size: 320.0,
         color: Color::BLACK,
     };
@@ -56,14 +82,9 @@ fn spawn_bg_text(
         .id()
 }
 
-fn spawn_fg_text(
-    commands: &mut Commands,
-    assets: &Res<GameAssets>,
-    text: &str,
-    offset: Vec3,
-) -> Entity {
+fn spawn_fg_text(commands: &mut Commands, locale: &Locale, text: &str, offset: Vec3) -> Entity {
     let text_style = TextStyle {
-        font: assets.silver_font.clone(),
+        font: locale.font(),
         font_size: 320.0,
         color: Color::WHITE,
     };
@@ -78,13 +99,27 @@ fn spawn_fg_text(
         .id()
 }
 
+/// Position of the socket on the level grid.
+fn socket_position(transform: &Transform) -> [i32; 2] {
+    let pos = (transform.translation.truncate() / 32.0).round();
+    [pos.x as i32, pos.y as i32]
+}
+
 fn spawn_aspect_sockets(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    locale: Res<Locale>,
+    current_level: Res<CurrentLevel>,
+    socket_memory: Res<SocketMemory>,
     q_items: Query<(&AspectSocketInitiater, &GridCoords), Added<AspectSocketInitiater>>,
 ) {
     for (aspect_initiater, grid_coords) in &q_items {
-        let aspect = aspect_initiater.aspect;
+        let key = (current_level.0.clone(), [grid_coords.x, grid_coords.y]);
+        let aspect = socket_memory
+            .0
+            .get(&key)
+            .copied()
+            .unwrap_or(aspect_initiater.aspect);
         let on_top = aspect_initiater.on_top;
 
         let pos = Vec3::new(
@@ -120,19 +155,21 @@ fn spawn_aspect_sockets(
             assets.aspect_socket_texture_right.clone()
         };
 
-        let aspect_string = aspect.to_string();
+        let aspect_string = locale.aspect_name(aspect);
         let offset = if on_top {
             ASPECT_TEXT_OFFSET_TOP
         } else {
             ASPECT_TEXT_OFFSET_BOTTOM
         };
-        let fg_text = spawn_fg_text(&mut commands, &assets, &aspect_string, offset);
-        let bg_text = spawn_bg_text(&mut commands, &assets, &aspect_string, offset);
+        let fg_text = spawn_fg_text(&mut commands, &locale, &aspect_string, offset);
+        let bg_text = spawn_bg_text(&mut commands, &locale, &aspect_string, offset);
         commands.entity(bg_text).insert(AspectNameText);
         commands.entity(fg_text).insert(AspectNameText);
 
         commands
             .spawn((
+                RunScoped,
+                LevelScoped,
                 YSort(0.0),
                 Socket { aspect, on_top },
                 SpriteBundle {
@@ -152,6 +189,7 @@ fn spawn_aspect_sockets(
 fn spawn_combiner_socket(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    locale: Res<Locale>,
     q_items: Query<&GridCoords, Added<AspectCombinerInitiater>>,
 ) {
     for grid_coords in &q_items {
@@ -185,13 +223,13 @@ fn spawn_combiner_socket(
 
         let fg_text = spawn_fg_text(
             &mut commands,
-            &assets,
+            &locale,
             "SHOULD NEVER HAPPEN",
             COMBINED_ASPECT_TEXT_OFFSET,
         );
         let bg_text = spawn_bg_text(
             &mut commands,
-            &assets,
+            &locale,
             "SHOULD NEVER HAPPEN",
             COMBINED_ASPECT_TEXT_OFFSET,
         );
@@ -200,6 +238,8 @@ fn spawn_combiner_socket(
 
         commands
             .spawn((
+                RunScoped,
+                LevelScoped,
                 YSort(0.0),
                 AspectCombiner,
                 SpriteBundle {
@@ -216,8 +256,22 @@ fn spawn_combiner_socket(
     }
 }
 
+fn remember_socket_aspects(
+    current_level: Res<CurrentLevel>,
+    mut socket_memory: ResMut<SocketMemory>,
+    q_sockets: Query<(&Transform, &Socket), Changed<Socket>>,
+) {
+    for (transform, socket) in &q_sockets {
+        socket_memory.0.insert(
+            (current_level.0.clone(), socket_position(transform)),
+            socket.aspect,
+        );
+    }
+}
+
 fn highlight_sockets(
     combiner: Res<Combiner>,
+    recipes: Res<AspectRecipes>,
     q_player: Query<&Transform, With<Player>>,
     mut q_sockets: Query<(&Transform, &mut TextureAtlas, &Socket), Without<Player>>,
 ) {
@@ -234,7 +288,7 @@ fn highlight_sockets(
             continue;
         }
 
-        let index = if is_socket_combination_possible(&combiner, socket)
+        let index = if is_socket_combination_possible(&combiner, &recipes, socket)
             && transform
                 .translation
                 .truncate()
@@ -251,6 +305,7 @@ fn highlight_sockets(
 
 fn highlight_combiner(
     combiner: Res<Combiner>,
+    recipes: Res<AspectRecipes>,
     q_player: Query<&Transform, With<Player>>,
     mut q_combiner: Query<(&Transform, &mut TextureAtlas), (With<AspectCombiner>, Without<Player>)>,
     q_sockets: Query<&Socket>,
@@ -272,7 +327,7 @@ fn highlight_combiner(
             return;
         };
 
-    let combined_aspect = aspect_combinations(&left_aspect, &right_aspect);
+    let combined_aspect = aspect_combinations(&recipes, &left_aspect, &right_aspect);
     let mut aspect_already_exists = false;
     for socket in &q_sockets {
         // The combined aspect was already combined and exists on of of the sockets
@@ -297,74 +352,109 @@ fn highlight_combiner(
     atlas.index = index;
 }
 
+/// Set the aspect of the socket and update its icon and name texts accordingly.
+pub fn set_socket_aspect(
+    assets: &Res<GameAssets>,
+    locale: &Locale,
+    children: &Children,
+    socket: &mut Socket,
+    aspect: Aspect,
+    q_icons: &mut Query<&mut Handle<Image>, With<AspectIcon>>,
+    q_texts: &mut Query<&mut Text, With<AspectNameText>>,
+) {
+    socket.aspect = aspect;
+    for child in children {
+        if let Ok(mut icon) = q_icons.get_mut(*child) {
+            *icon = icon_texture(assets, &aspect);
+        } else if let Ok(mut text) = q_texts.get_mut(*child) {
+            text.sections[0].value = locale.aspect_name(aspect);
+        }
+    }
+}
+
+/// Put the aspect into the leftmost empty socket of the given row, returns the filled socket.
 fn set_visuals_for_socket(
     assets: &Res<GameAssets>,
-    combiner: &Res<Combiner>,
-    q_sockets: &mut Query<(&Children, &Transform, &mut Socket), Without<Player>>,
+    locale: &Locale,
+    aspect: Aspect,
+    q_sockets: &mut Query<(Entity, &Children, &Transform, &mut Socket), Without<Player>>,
     q_icons: &mut Query<&mut Handle<Image>, With<AspectIcon>>,
     q_texts: &mut Query<&mut Text, With<AspectNameText>>,
     on_top: bool,
-) {
-    if let Some((children, _, mut socket)) = q_sockets
+) -> Option<Entity> {
+    let (entity, children, _, mut socket) = q_sockets
         .iter_mut()
-        .filter(|(_, _, socket)| socket.aspect == Aspect::NotImplemented && socket.on_top == on_top)
-        .min_by(|(_, x_transform, _), (_, y_transform, _)| {
+        .filter(|(_, _, _, socket)| {
+            socket.aspect == Aspect::NotImplemented && socket.on_top == on_top
+        })
+        .min_by(|(_, _, x_transform, _), (_, _, y_transform, _)| {
             x_transform
                 .translation
                 .x
                 .total_cmp(&y_transform.translation.x)
-        })
-    {
-        socket.aspect = combiner.last_combined_aspect;
-        for child in children {
-            if let Ok(mut icon) = q_icons.get_mut(*child) {
-                *icon = icon_texture(assets, &combiner.last_combined_aspect);
-            } else if let Ok(mut text) = q_texts.get_mut(*child) {
-                text.sections[0].value = combiner.last_combined_aspect.to_string();
-            }
-        }
-    }
+        })?;
+    set_socket_aspect(
+        assets,
+        locale,
+        children,
+        &mut socket,
+        aspect,
+        q_icons,
+        q_texts,
+    );
+    Some(entity)
 }
 
 fn push_combined_aspect(
     assets: Res<GameAssets>,
-    combiner: Res<Combiner>,
-    mut q_sockets: Query<(&Children, &Transform, &mut Socket), Without<Player>>,
+    locale: Res<Locale>,
+    mut history: ResMut<CombinationHistory>,
+    mut q_sockets: Query<(Entity, &Children, &Transform, &mut Socket), Without<Player>>,
     mut q_icons: Query<&mut Handle<Image>, With<AspectIcon>>,
     mut q_texts: Query<&mut Text, With<AspectNameText>>,
+    mut ev_combined_aspect: EventReader<CombinedAspect>,
 ) {
-    set_visuals_for_socket(
-        &assets,
-        &combiner,
-        &mut q_sockets,
-        &mut q_icons,
-        &mut q_texts,
-        true,
-    );
-    set_visuals_for_socket(
-        &assets,
-        &combiner,
-        &mut q_sockets,
-        &mut q_icons,
-        &mut q_texts,
-        false,
-    );
+    for ev in ev_combined_aspect.read() {
+        let sockets = [true, false]
+            .into_iter()
+            .filter_map(|on_top| {
+                set_visuals_for_socket(
+                    &assets,
+                    &locale,
+                    ev.result,
+                    &mut q_sockets,
+                    &mut q_icons,
+                    &mut q_texts,
+                    on_top,
+                )
+            })
+            .collect();
+        history.push(CombinationRecord {
+            left: ev.left,
+            right: ev.right,
+            result: ev.result,
+            sockets,
+        });
+    }
 }
 
 pub struct AspectSocketPlugin;
 
 impl Plugin for AspectSocketPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                spawn_aspect_sockets,
-                spawn_combiner_socket,
-                highlight_sockets,
-                highlight_combiner,
-                push_combined_aspect.run_if(on_event::<CombinedAspect>()),
-            )
-                .run_if(in_state(GameState::Gaming)),
-        );
+        app.init_resource::<SocketMemory>()
+            .add_systems(OnEnter(GameState::Intro), reset_resource::<SocketMemory>)
+            .add_systems(
+                Update,
+                (
+                    spawn_aspect_sockets,
+                    spawn_combiner_socket,
+                    highlight_sockets,
+                    highlight_combiner,
+                    push_combined_aspect.run_if(on_event::<CombinedAspect>()),
+                    remember_socket_aspects,
+                )
+                    .run_if(in_state(GameState::Gaming)),
+            );
     }
 }
@@ -0,0 +1,156 @@
+//! Plays through every possible order of combinations, starting from the socket layout in the LDtk level.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs_ldtk::ldtk::LdtkJson;
+
+use crate::npc::endings::EndingRules;
+
+use super::{combiner::aspect_combinations, recipes::AspectRecipes, Aspect};
+
+/// The aspects of the sockets in both rows, from left to right.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SocketLayout {
+    pub top: Vec<Aspect>,
+    pub bottom: Vec<Aspect>,
+}
+
+impl SocketLayout {
+    /// The sockets of `level` as they are placed in the LDtk project.
+    pub fn from_ldtk(contents: &str, level: &str) -> Result<Self, String> {
+        let project: LdtkJson = serde_json::from_str(contents).map_err(|err| err.to_string())?;
+        let level = project
+            .levels
+            .iter()
+            .find(|l| l.identifier == level)
+            .ok_or(format!("there is no level '{}'", level))?;
+
+        let mut top = Vec::new();
+        let mut bottom = Vec::new();
+        for entity in level
+            .layer_instances
+            .iter()
+            .flatten()
+            .flat_map(|layer| layer.entity_instances.iter())
+            .filter(|entity| entity.identifier == "AspectSocket")
+        {
+            let aspect = entity
+                .get_enum_field("aspect")
+                .map_err(|err| err.to_string())?
+                .parse::<Aspect>()
+                .map_err(|err| err.to_string())?;
+            let on_top = *entity
+                .get_bool_field("on_top")
+                .map_err(|err| err.to_string())?;
+            let row = if on_top { &mut top } else { &mut bottom };
+            row.push((entity.grid.x, aspect));
+        }
+
+        let sorted = |mut row: Vec<(i32, Aspect)>| -> Vec<Aspect> {
+            row.sort_by_key(|(x, _)| *x);
+            row.into_iter().map(|(_, aspect)| aspect).collect()
+        };
+        Ok(Self {
+            top: sorted(top),
+            bottom: sorted(bottom),
+        })
+    }
+
+    pub fn aspects(&self) -> Vec<Aspect> {
+        self.top.iter().chain(self.bottom.iter()).copied().collect()
+    }
+
+    /// Same as `check_all_aspects_full`, this is when the bed appears.
+    pub fn is_full(&self) -> bool {
+        !self.aspects().contains(&Aspect::NotImplemented)
+    }
+
+    /// All aspects that can be combined next, each one only once.
+    /// Like `highlight_combiner`, aspects that are already in a socket can't be combined again.
+    pub fn possible_combinations(&self, recipes: &AspectRecipes) -> Vec<Aspect> {
+        let aspects = self.aspects();
+        let mut results = Vec::new();
+        for left in self.top.iter().filter(|a| **a != Aspect::NotImplemented) {
+            for right in self.bottom.iter().filter(|a| **a != Aspect::NotImplemented) {
+                let result = aspect_combinations(recipes, left, right);
+                if result != Aspect::NotImplemented
+                    && !aspects.contains(&result)
+                    && !results.contains(&result)
+                {
+                    results.push(result);
+                }
+            }
+        }
+        results
+    }
+
+    /// Same as `push_combined_aspect`, the result goes into the leftmost empty socket of each row.
+    pub fn push(&self, result: Aspect) -> Self {
+        let mut layout = self.clone();
+        for row in [&mut layout.top, &mut layout.bottom] {
+            if let Some(socket) = row.iter_mut().find(|a| **a == Aspect::NotImplemented) {
+                *socket = result;
+            }
+        }
+        layout
+    }
+}
+
+/// One way to play through the combiner.
+#[derive(Clone, Debug)]
+pub struct Playthrough {
+    /// The combinations in the order they were made.
+    pub combined_aspects: Vec<Aspect>,
+    pub layout: SocketLayout,
+}
+
+#[derive(Debug, Default)]
+pub struct Solution {
+    /// Playthroughs that filled all sockets, the bed appears at the end of these.
+    pub finished: Vec<Playthrough>,
+    /// Playthroughs with empty sockets left and no possible combination.
+    pub stuck: Vec<Playthrough>,
+}
+
+impl Solution {
+    /// How many of the finished playthroughs end in which narrator node.
+    pub fn ending_distribution(&self, rules: &EndingRules) -> BTreeMap<String, usize> {
+        let mut distribution = BTreeMap::new();
+        for playthrough in &self.finished {
+            let ending =
+                rules.determine(&playthrough.layout.aspects(), &playthrough.combined_aspects);
+            *distribution.entry(ending.node().to_string()).or_insert(0) += 1;
+        }
+        distribution
+    }
+}
+
+pub fn solve(layout: SocketLayout, recipes: &AspectRecipes) -> Solution {
+    let mut solution = Solution::default();
+    let mut open = vec![Playthrough {
+        combined_aspects: Vec::new(),
+        layout,
+    }];
+
+    while let Some(playthrough) = open.pop() {
+        if playthrough.layout.is_full() {
+            solution.finished.push(playthrough);
+            continue;
+        }
+
+        let combinations = playthrough.layout.possible_combinations(recipes);
+        if combinations.is_empty() {
+            solution.stuck.push(playthrough);
+            continue;
+        }
+        for result in combinations {
+            let mut combined_aspects = playthrough.combined_aspects.clone();
+            combined_aspects.push(result);
+            open.push(Playthrough {
+                combined_aspects,
+                layout: playthrough.layout.push(result),
+            });
+        }
+    }
+    solution
+}
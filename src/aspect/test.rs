@@ -0,0 +1,179 @@
+use std::fs;
+
+use crate::{
+    harness::{shipped_recipes, shipped_rules},
+    npc::narrator::NarratorDialogue,
+};
+
+use super::{
+    recipes::{AspectRecipes, RecipeError},
+    solver::{solve, SocketLayout, Solution},
+    Aspect, Combiner,
+};
+
+#[test]
+fn validate_shipped_recipes() {
+    assert!(!shipped_recipes().recipes().is_empty());
+}
+
+#[test]
+fn validate_unordered_recipes_work_both_ways() {
+    let recipes = shipped_recipes();
+    for recipe in recipes.recipes().iter().filter(|r| !r.ordered) {
+        assert_eq!(recipes.combine(&recipe.left, &recipe.right), recipe.result);
+        assert_eq!(recipes.combine(&recipe.right, &recipe.left), recipe.result);
+    }
+}
+
+#[test]
+fn validate_ordered_recipes_only_work_one_way() {
+    let recipes = AspectRecipes::from_ron(
+        br#"[(left: "Joy", right: "Sadness", result: "Nostalgia", ordered: true)]"#,
+    )
+    .unwrap();
+    assert_eq!(
+        recipes.combine(&Aspect::Joy, &Aspect::Sadness),
+        Aspect::Nostalgia
+    );
+    assert_eq!(
+        recipes.combine(&Aspect::Sadness, &Aspect::Joy),
+        Aspect::NotImplemented
+    );
+}
+
+#[test]
+fn reject_unknown_aspects() {
+    let result = AspectRecipes::from_ron(br#"[(left: "Joy", right: "Boredom", result: "Envy")]"#);
+    assert!(matches!(result, Err(RecipeError::UnknownAspect(name)) if name == "Boredom"));
+
+    let result =
+        AspectRecipes::from_ron(br#"[(left: "Joy", right: "NotImplemented", result: "Envy")]"#);
+    assert!(matches!(result, Err(RecipeError::UnknownAspect(_))));
+}
+
+#[test]
+fn reject_duplicate_pairs() {
+    let result = AspectRecipes::from_ron(
+        br#"[
+            (left: "Joy", right: "Sadness", result: "Nostalgia"),
+            (left: "Sadness", right: "Joy", result: "Melancholy"),
+        ]"#,
+    );
+    assert!(matches!(result, Err(RecipeError::DuplicatePair(..))));
+
+    let result = AspectRecipes::from_ron(
+        br#"[
+            (left: "Joy", right: "Sadness", result: "Nostalgia", ordered: true),
+            (left: "Sadness", right: "Joy", result: "Melancholy", ordered: true),
+        ]"#,
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn reject_cycles() {
+    let result = AspectRecipes::from_ron(
+        br#"[
+            (left: "Joy", right: "Sadness", result: "Nostalgia"),
+            (left: "Nostalgia", right: "Fear", result: "Joy"),
+        ]"#,
+    );
+    assert!(matches!(result, Err(RecipeError::Cycle(_))));
+}
+
+#[test]
+fn validate_undo_last_combination() {
+    let mut combiner = Combiner {
+        last_combined_aspect: Aspect::Motivation,
+        all_sockets_full: true,
+        combined_aspects: vec![Aspect::Nostalgia, Aspect::Motivation],
+        ..Default::default()
+    };
+
+    combiner.undo_last_combination();
+    assert_eq!(combiner.combined_aspects, vec![Aspect::Nostalgia]);
+    assert_eq!(combiner.last_combined_aspect, Aspect::Nostalgia);
+    assert!(!combiner.all_sockets_full);
+
+    combiner.undo_last_combination();
+    assert!(combiner.combined_aspects.is_empty());
+    assert_eq!(combiner.last_combined_aspect, Aspect::NotImplemented);
+}
+
+const PATH_TO_LEVEL: &str = "assets/map/level.ldtk";
+const COMBINER_LEVEL: &str = "Level_0";
+
+fn shipped_solution() -> Solution {
+    let contents =
+        fs::read_to_string(PATH_TO_LEVEL).expect("Should have been able to read the level");
+    let layout = SocketLayout::from_ldtk(&contents, COMBINER_LEVEL).unwrap();
+    let recipes = shipped_recipes();
+    solve(layout, &recipes)
+}
+
+#[test]
+fn validate_no_playthrough_gets_stuck() {
+    let solution = shipped_solution();
+    assert!(!solution.finished.is_empty());
+    if let Some(playthrough) = solution.stuck.first() {
+        panic!(
+            "combining {:?} leaves empty sockets without any possible combination",
+            playthrough.combined_aspects
+        );
+    }
+}
+
+#[test]
+fn validate_reachable_endings() {
+    let rules = shipped_rules();
+    let distribution = shipped_solution().ending_distribution(&rules);
+    // Shows up with `cargo test -- --nocapture`, to see how balanced the endings are.
+    eprintln!("playthroughs per ending: {:?}", distribution);
+
+    assert!(distribution.contains_key(NarratorDialogue::GOOD_ENDING));
+    for rule in rules.rules() {
+        assert!(
+            distribution.contains_key(&rule.node),
+            "no playthrough ends in {}, playthroughs per ending: {:?}",
+            rule.node,
+            distribution
+        );
+    }
+}
+
+#[test]
+fn validate_solver_prevents_duplicates() {
+    let recipes = shipped_recipes();
+    let layout = SocketLayout {
+        top: vec![Aspect::Joy, Aspect::NotImplemented, Aspect::NotImplemented],
+        bottom: vec![
+            Aspect::Sadness,
+            Aspect::NotImplemented,
+            Aspect::NotImplemented,
+        ],
+    };
+    let solution = solve(layout, &recipes);
+
+    // Nostalgia first, then either Motivation or Melancholy, but never Nostalgia a second time.
+    assert!(solution.stuck.is_empty());
+    assert_eq!(solution.finished.len(), 2);
+    for playthrough in &solution.finished {
+        assert_eq!(playthrough.combined_aspects[0], Aspect::Nostalgia);
+        assert_ne!(playthrough.combined_aspects[1], Aspect::Nostalgia);
+        assert!(playthrough.layout.is_full());
+    }
+}
+
+#[test]
+fn validate_solver_finds_stuck_layouts() {
+    let recipes = shipped_recipes();
+    let layout = SocketLayout {
+        top: vec![Aspect::Anger, Aspect::NotImplemented],
+        bottom: vec![Aspect::Joy, Aspect::NotImplemented],
+    };
+    let solution = solve(layout.clone(), &recipes);
+
+    assert!(solution.finished.is_empty());
+    assert_eq!(solution.stuck.len(), 1);
+    assert_eq!(solution.stuck[0].layout, layout);
+}
@@ -4,7 +4,15 @@ use bevy_ecs_ldtk::prelude::LdtkProject;
 use bevy_kira_audio::AudioSource;
 use bevy_trickfilm::prelude::*;
 
+use crate::{
+    aspect::AspectRecipes,
+    locale::StringTable,
+    npc::{characters::Characters, endings::EndingRules},
+};
+
+/// `Default` gives placeholder handles, it's what the headless test app runs with.
 #[derive(AssetCollection, Resource)]
+#[cfg_attr(test, derive(Default))]
 pub struct GameAssets {
     // --- CHARACTERS ---
     #[asset(path = "characters/ami.png")]
@@ -61,6 +69,14 @@ pub struct GameAssets {
     #[asset(path = "aspects/forgiveness_icon.png")]
     pub forgiveness_icon: Handle<Image>,
 
+    #[asset(path = "data/aspect.recipes.ron")]
+    pub aspect_recipes: Handle<AspectRecipes>,
+    #[asset(path = "data/narrator.endings.ron")]
+    pub ending_rules: Handle<EndingRules>,
+    /// Portraits and blips of the characters are loaded along with it.
+    #[asset(path = "data/dialogue.characters.ron")]
+    pub characters: Handle<Characters>,
+
     // --- EFFECTS ---
     #[asset(path = "effects/smoke.png")]
     pub smoke_texture: Handle<Image>,
@@ -118,6 +134,14 @@ pub struct GameAssets {
     pub ui_right_key_texture: Handle<Image>,
     #[asset(texture_atlas(tile_size_x = 34, tile_size_y = 34, columns = 3, rows = 1))]
     pub ui_right_key_layout: Handle<TextureAtlasLayout>,
+    #[asset(path = "ui/keys/blank_key.png")]
+    pub ui_blank_key_texture: Handle<Image>,
+    #[asset(texture_atlas(tile_size_x = 34, tile_size_y = 34, columns = 2, rows = 1))]
+    pub ui_blank_key_layout: Handle<TextureAtlasLayout>,
+    #[asset(path = "ui/keys/blank_button.png")]
+    pub ui_blank_button_texture: Handle<Image>,
+    #[asset(texture_atlas(tile_size_x = 34, tile_size_y = 34, columns = 2, rows = 1))]
+    pub ui_blank_button_layout: Handle<TextureAtlasLayout>,
     #[asset(path = "ui/keys/shift_key.png")]
     pub ui_shift_key_texture: Handle<Image>,
     #[asset(texture_atlas(tile_size_x = 66, tile_size_y = 34, columns = 2, rows = 1))]
@@ -141,20 +165,10 @@ pub struct GameAssets {
     #[asset(path = "ui/dialogue_continue.png")]
     pub dialogue_continue: Handle<Image>,
 
-    #[asset(path = "ui/ami_character_icon.png")]
-    pub ami_character_icon: Handle<Image>,
-    #[asset(path = "ui/ima_character_icon.png")]
-    pub ima_character_icon: Handle<Image>,
-
     #[asset(path = "ui/vignette.png")]
     pub vignette: Handle<Image>,
 
     // --- AUDIO ---
-    #[asset(path = "audio/ami_blip.ogg")]
-    pub ami_blip_sound: Handle<AudioSource>,
-    #[asset(path = "audio/ima_blip.ogg")]
-    pub ima_blip_sound: Handle<AudioSource>,
-
     #[asset(path = "audio/koto.ogg")]
     pub koto_hit_sound: Handle<AudioSource>,
     #[asset(path = "audio/footstep.ogg")]
@@ -169,6 +183,12 @@ pub struct GameAssets {
     #[asset(path = "audio/ending_bgm.ogg")]
     pub ending_bgm: Handle<AudioSource>,
 
+    // --- LOCALE ---
+    #[asset(path = "locale/en-US.strings.ron")]
+    pub english_strings: Handle<StringTable>,
+    #[asset(path = "locale/de-DE.strings.ron")]
+    pub german_strings: Handle<StringTable>,
+
     // --- FONT ---
     #[asset(path = "fonts/PressStart2P.ttf")]
     pub pixel_font: Handle<Font>,
@@ -2,21 +2,39 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
+use strum_macros::EnumString;
 
-use crate::{npc::narrator::TriggeredNarratorDialogue, GameAssets, GameState};
+use crate::{npc::narrator::TriggeredNarratorDialogue, run::RestartRun, GameAssets, GameState};
 
-use super::GameAudio;
+use super::{AudioBus, GameAudio};
 
 const MAIN_BGM_VOLUME: f64 = 0.15;
 const ENDING_BGM_VOLUME: f64 = 0.6;
 const BGM_FADE_OUT: f32 = 4.0;
 
+/// The tracks `SetBgm` can switch to, `None` just stops the current BGM.
+#[derive(Clone, Copy, PartialEq, Debug, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum BgmTrack {
+    Main,
+    Ending,
+    None,
+}
+
+/// Fade out the playing BGMs and start the given track instead.
+#[derive(Event)]
+pub struct SetBgm(pub BgmTrack);
+
 #[derive(Component)]
 struct Bgm {
     handle: Handle<AudioInstance>,
     volume: f64,
 }
 
+/// The looping BGM, it keeps playing when going from the title screen into the game.
+#[derive(Component)]
+struct MainBgm;
+
 #[derive(Component, Deref, DerefMut)]
 struct UnmuteTimer(Timer);
 
@@ -26,24 +44,57 @@ impl Default for UnmuteTimer {
     }
 }
 
-fn spawn_main_bgm(
-    mut commands: Commands,
-    assets: Res<GameAssets>,
-    audio: Res<Audio>,
-    game_audio: Res<GameAudio>,
+fn play_main_bgm(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    audio: &Audio,
+    game_audio: &GameAudio,
 ) {
-    let volume = game_audio.main_volume * MAIN_BGM_VOLUME;
+    let volume = game_audio.volume(AudioBus::Music) * MAIN_BGM_VOLUME;
     let handle = audio
         .play(assets.main_bgm.clone())
         .with_volume(volume)
         .looped()
         .handle();
+    commands.spawn((
+        MainBgm,
+        Bgm {
+            handle,
+            volume: MAIN_BGM_VOLUME,
+        },
+    ));
+}
+
+fn play_ending_bgm(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    audio: &Audio,
+    game_audio: &GameAudio,
+) {
+    let volume = game_audio.volume(AudioBus::Music) * ENDING_BGM_VOLUME;
+    let handle = audio
+        .play(assets.ending_bgm.clone())
+        .with_volume(volume)
+        .handle();
     commands.spawn(Bgm {
         handle,
-        volume: MAIN_BGM_VOLUME,
+        volume: ENDING_BGM_VOLUME,
     });
 }
 
+fn spawn_main_bgm(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    audio: Res<Audio>,
+    game_audio: Res<GameAudio>,
+    q_main_bgms: Query<(), With<MainBgm>>,
+) {
+    if !q_main_bgms.is_empty() {
+        return;
+    }
+    play_main_bgm(&mut commands, &assets, &audio, &game_audio);
+}
+
 fn update_bgm_volumes(
     game_audio: Res<GameAudio>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
@@ -51,22 +102,41 @@ fn update_bgm_volumes(
 ) {
     for bgm in &q_bgms {
         if let Some(instance) = audio_instances.get_mut(bgm.handle.id()) {
-            let volume = game_audio.main_volume * bgm.volume;
+            let volume = game_audio.volume(AudioBus::Music) * bgm.volume;
             instance.set_volume(volume, AudioTween::default());
         }
     }
 }
 
+fn fade_out_bgm(
+    commands: &mut Commands,
+    audio_instances: &mut Assets<AudioInstance>,
+    entity: Entity,
+    bgm: &Bgm,
+) {
+    if let Some(instance) = audio_instances.get_mut(bgm.handle.id()) {
+        instance.stop(AudioTween::linear(Duration::from_secs_f32(BGM_FADE_OUT)));
+    }
+    commands.entity(entity).despawn_recursive();
+}
+
 fn despawn_bgms(
     mut commands: Commands,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
     q_bgms: Query<(Entity, &Bgm)>,
 ) {
     for (entity, bgm) in &q_bgms {
-        if let Some(instance) = audio_instances.get_mut(bgm.handle.id()) {
-            instance.stop(AudioTween::linear(Duration::from_secs_f32(BGM_FADE_OUT)));
-        }
-        commands.entity(entity).despawn_recursive();
+        fade_out_bgm(&mut commands, &mut audio_instances, entity, bgm);
+    }
+}
+
+fn despawn_other_bgms(
+    mut commands: Commands,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    q_bgms: Query<(Entity, &Bgm), Without<MainBgm>>,
+) {
+    for (entity, bgm) in &q_bgms {
+        fade_out_bgm(&mut commands, &mut audio_instances, entity, bgm);
     }
 }
 
@@ -76,24 +146,47 @@ fn spawn_ending_bgm(
     audio: Res<Audio>,
     game_audio: Res<GameAudio>,
 ) {
-    let volume = game_audio.main_volume * ENDING_BGM_VOLUME;
-    let handle = audio
-        .play(assets.ending_bgm.clone())
-        .with_volume(volume)
-        .handle();
-    commands.spawn(Bgm {
-        handle,
-        volume: ENDING_BGM_VOLUME,
-    });
+    play_ending_bgm(&mut commands, &assets, &audio, &game_audio);
+}
+
+fn set_bgm(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    audio: Res<Audio>,
+    game_audio: Res<GameAudio>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    q_bgms: Query<(Entity, &Bgm)>,
+    mut ev_set_bgm: EventReader<SetBgm>,
+) {
+    let track = match ev_set_bgm.read().last() {
+        Some(r) => r.0,
+        None => return,
+    };
+
+    for (entity, bgm) in &q_bgms {
+        fade_out_bgm(&mut commands, &mut audio_instances, entity, bgm);
+    }
+    match track {
+        BgmTrack::Main => play_main_bgm(&mut commands, &assets, &audio, &game_audio),
+        BgmTrack::Ending => play_ending_bgm(&mut commands, &assets, &audio, &game_audio),
+        BgmTrack::None => {}
+    }
 }
 
 pub struct BgmPlugin;
 
 impl Plugin for BgmPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Gaming), spawn_main_bgm)
+        app.add_event::<SetBgm>()
+            .add_systems(
+                OnEnter(GameState::Title),
+                (despawn_other_bgms, spawn_main_bgm),
+            )
+            .add_systems(OnEnter(GameState::Gaming), spawn_main_bgm)
             .add_systems(OnEnter(GameState::Ending), despawn_bgms)
             .add_systems(Update, update_bgm_volumes)
+            .add_systems(Update, set_bgm.run_if(on_event::<SetBgm>()))
+            .add_systems(Update, despawn_bgms.run_if(on_event::<RestartRun>()))
             .add_systems(
                 Update,
                 (spawn_ending_bgm.run_if(on_event::<TriggeredNarratorDialogue>()))
@@ -6,15 +6,18 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
 
+pub use bgm::{BgmTrack, SetBgm};
 #[allow(unused_imports)]
 pub use sound::PlaySound;
 
-use crate::player::input::PlayerInput;
+use crate::utils::storage;
 
-const MAIN_VOLUME_DELTA: f64 = 0.05;
+const CONFIG_FILE: &str = "audio.ron";
 const FADE_IN_TIME: f32 = 3.0;
-const DEFAULT_VOLUME: f64 = 0.5;
+const DEFAULT_MASTER_VOLUME: f64 = 0.5;
 
 pub struct GameAudioPlugin;
 
@@ -26,36 +29,102 @@ impl Plugin for GameAudioPlugin {
                 sound::GameSoundPlugin,
                 bgm::BgmPlugin,
             ))
-            .init_resource::<GameAudio>()
-            .add_systems(Update, (update_main_volume, fade_in_volume));
+            .insert_resource(GameAudio::load())
+            .add_systems(Update, fade_in_volume);
     }
 }
 
-#[derive(Resource)]
+/// The channel a sound gets mixed into, every bus has its own volume on top of the master volume.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, EnumIter)]
+pub enum AudioBus {
+    Music,
+    #[default]
+    Sfx,
+    Blip,
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct GameAudio {
-    pub main_volume: f64,
+    master_volume: f64,
+    music_volume: f64,
+    sfx_volume: f64,
+    blip_volume: f64,
+    /// Ramps up from zero when the game starts, not part of the settings.
+    #[serde(skip)]
+    fade_in: f64,
 }
 
 impl Default for GameAudio {
     fn default() -> Self {
         Self {
-            main_volume: DEFAULT_VOLUME,
+            master_volume: DEFAULT_MASTER_VOLUME,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            blip_volume: 1.0,
+            fade_in: 0.0,
         }
     }
 }
 
 impl GameAudio {
-    pub fn update(&mut self, x: f64) {
-        self.main_volume = (self.main_volume + x).clamp(0.0, 1.0);
+    /// The volume that all sounds routed through `bus` get multiplied with.
+    pub fn volume(&self, bus: AudioBus) -> f64 {
+        self.fade_in * self.master_volume * self.bus_volume(bus)
     }
-}
 
-fn update_main_volume(player_input: Res<PlayerInput>, mut game_audio: ResMut<GameAudio>) {
-    if player_input.scroll == 0.0 {
-        return;
+    pub fn master_volume(&self) -> f64 {
+        self.master_volume
+    }
+
+    pub fn set_master_volume(&mut self, volume: f64) {
+        self.master_volume = volume.clamp(0.0, 1.0);
+    }
+
+    pub fn bus_volume(&self, bus: AudioBus) -> f64 {
+        match bus {
+            AudioBus::Music => self.music_volume,
+            AudioBus::Sfx => self.sfx_volume,
+            AudioBus::Blip => self.blip_volume,
+        }
     }
 
-    game_audio.update(-player_input.scroll as f64 * MAIN_VOLUME_DELTA);
+    pub fn set_bus_volume(&mut self, bus: AudioBus, volume: f64) {
+        let volume = volume.clamp(0.0, 1.0);
+        match bus {
+            AudioBus::Music => self.music_volume = volume,
+            AudioBus::Sfx => self.sfx_volume = volume,
+            AudioBus::Blip => self.blip_volume = volume,
+        }
+    }
+
+    /// Load the volume settings from the config file, falls back to the defaults.
+    pub fn load() -> Self {
+        let contents = match storage::read(CONFIG_FILE) {
+            Some(r) => r,
+            None => return Self::default(),
+        };
+
+        match ron::from_str::<Self>(&contents) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to parse audio config, using defaults, {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to serialize audio config, {}", err);
+                return;
+            }
+        };
+        if let Err(err) = storage::write(CONFIG_FILE, &contents) {
+            error!("failed to write audio config, {}", err);
+        }
+    }
 }
 
 fn fade_in_volume(
@@ -76,11 +145,10 @@ fn fade_in_volume(
     }
 
     timer.tick(time.delta());
-    game_audio.main_volume =
-        timer.elapsed().as_secs_f64() / timer.duration().as_secs_f64() * DEFAULT_VOLUME;
+    game_audio.fade_in = timer.elapsed().as_secs_f64() / timer.duration().as_secs_f64();
 
     if timer.just_finished() {
-        game_audio.main_volume = DEFAULT_VOLUME;
+        game_audio.fade_in = 1.0;
         *is_finished = true;
     }
 }
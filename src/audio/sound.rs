@@ -1,19 +1,21 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use bevy::{prelude::*, utils::HashSet};
 use bevy_kira_audio::prelude::{AudioSource, *};
 
-use crate::GameState;
+use crate::{GameRng, GameState};
 
-use super::{spacial::SpacialSound, GameAudio};
+use super::{spacial::SpacialSound, AudioBus, GameAudio};
 
 #[derive(Resource, Deref, DerefMut, Default)]
-struct RepeatingSounds(Vec<(f64, Handle<AudioInstance>)>);
+struct RepeatingSounds(Vec<(f64, AudioBus, Handle<AudioInstance>)>);
 
 #[derive(Event)]
 pub struct PlaySound {
     pub clip: Handle<AudioSource>,
     pub volume: f64,
+    /// The bus this sound is mixed into, its volume is set in the settings.
+    pub bus: AudioBus,
     /// Playback rate, default is 1.0.
     pub playback_rate: f64,
     /// Playback offset intensity. This will add a random offset
@@ -31,6 +33,7 @@ impl Default for PlaySound {
         Self {
             clip: Handle::default(),
             volume: 1.0,
+            bus: AudioBus::default(),
             playback_rate: 1.0,
             rand_speed_intensity: 0.0,
             repeat: false,
@@ -45,9 +48,9 @@ fn play_sounds(
     audio: Res<Audio>,
     game_audio: Res<GameAudio>,
     mut repeating_sounds: ResMut<RepeatingSounds>,
+    mut rng: ResMut<GameRng>,
     mut ev_play_sound: EventReader<PlaySound>,
 ) {
-    let mut rng = thread_rng();
     let mut added_sounds: HashSet<Handle<AudioSource>> = HashSet::new();
 
     for ev in ev_play_sound.read() {
@@ -66,14 +69,14 @@ fn play_sounds(
         let mut audio_command = audio.play(ev.clip.clone());
         let sound_volume = ev.volume * volume_offset;
         audio_command
-            .with_volume(sound_volume * game_audio.main_volume)
+            .with_volume(sound_volume * game_audio.volume(ev.bus))
             .with_playback_rate(ev.playback_rate + speed_offset);
 
         let audio_instance = audio_command.handle();
 
         if ev.repeat {
             audio_command.looped();
-            repeating_sounds.push((sound_volume, audio_instance.clone()));
+            repeating_sounds.push((sound_volume, ev.bus, audio_instance.clone()));
         }
         if ev.reverse {
             audio_command.reverse();
@@ -83,7 +86,7 @@ fn play_sounds(
             let audio_emitter = commands
                 .spawn((
                     TransformBundle::default(),
-                    SpacialSound::new(ev.volume),
+                    SpacialSound::new(ev.volume, ev.bus),
                     AudioEmitter {
                         instances: vec![audio_instance],
                     },
@@ -108,10 +111,10 @@ fn update_repeating_sounds(
     mut repeating_sounds: ResMut<RepeatingSounds>,
 ) {
     let mut invalid_indices = vec![];
-    for (index, (volume, instance)) in repeating_sounds.iter().enumerate() {
+    for (index, (volume, bus, instance)) in repeating_sounds.iter().enumerate() {
         match audio_instances.get_mut(instance) {
             Some(r) => {
-                r.set_volume(volume * game_audio.main_volume, AudioTween::default());
+                r.set_volume(volume * game_audio.volume(*bus), AudioTween::default());
             }
             None => {
                 invalid_indices.push(index);
@@ -129,7 +132,6 @@ pub struct GameSoundPlugin;
 impl Plugin for GameSoundPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<PlaySound>()
-            .init_resource::<GameAudio>()
             .init_resource::<RepeatingSounds>()
             .add_systems(
                 Update,
@@ -1,18 +1,19 @@
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 
-use super::GameAudio;
+use super::{AudioBus, GameAudio};
 
 const MAX_DISTANCE: f64 = 250.0;
 
 #[derive(Component)]
 pub struct SpacialSound {
     volume: f64,
+    bus: AudioBus,
 }
 
 impl SpacialSound {
-    pub fn new(volume: f64) -> Self {
-        Self { volume }
+    pub fn new(volume: f64, bus: AudioBus) -> Self {
+        Self { volume, bus }
     }
 }
 
@@ -27,7 +28,7 @@ fn update(
             .truncate()
             .length_squared();
         let multiplier = (1.0 - distance as f64 / MAX_DISTANCE.powi(2)).clamp(0.0, 1.0);
-        let volume: f64 = sound.volume * multiplier.powi(2) * game_audio.main_volume;
+        let volume: f64 = sound.volume * multiplier.powi(2) * game_audio.volume(sound.bus);
 
         for instance in emitter.instances.iter() {
             if let Some(instance) = audio_instances.get_mut(instance) {
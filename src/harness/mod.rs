@@ -0,0 +1,332 @@
+//! A headless app to run the game systems in integration tests, without a window, GPU or audio.
+
+#[cfg(test)]
+mod test;
+
+use std::{fs, time::Duration};
+
+use bevy::{asset::AssetPlugin, prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+use bevy_ecs_ldtk::{
+    prelude::{LevelEvent, LevelSelection},
+    GridCoords,
+};
+use bevy_yarnspinner::events::DialogueCompleteEvent;
+
+use crate::{
+    aspect::{Aspect, AspectCombinerInitiater, AspectRecipes, AspectSocketInitiater, Socket},
+    audio::PlaySound,
+    locale::{Language, Locale, StringTable},
+    npc::{characters::Characters, endings::EndingRules},
+    player::{
+        input::PlayerInput,
+        replay::{set_replay_mode, InputRecording, ReplayMode, ReplayPlugin},
+        Player, PLAYER_PIVOT,
+    },
+    run::RestartRun,
+    save::GameLoaded,
+    ui::{
+        dialogue::{runner::TimeSinceGaming, variables::DialogueVariables},
+        FadeScreen, ScreenFaded,
+    },
+    world::{
+        Bed, CurrentLevel, LevelEntered, LevelEntrance, LevelExit, LevelScoped, TutorialProgress,
+    },
+    GameAssets, GameState, PauseState,
+};
+
+const PATH_TO_RECIPES: &str = "assets/data/aspect.recipes.ron";
+const PATH_TO_ENDING_RULES: &str = "assets/data/narrator.endings.ron";
+const PATH_TO_CHARACTERS: &str = "assets/data/dialogue.characters.ron";
+const PATH_TO_LOCALE: &str = "assets/locale";
+const TILE_SIZE: f32 = 32.0;
+/// Every frame advances the clock by this much, so that timers don't depend on how fast tests run.
+const FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// The events of type `E` that were sent since the app was built.
+#[derive(Resource)]
+struct RecordedEvents<E: Event + Clone>(Vec<E>);
+
+fn record_events<E: Event + Clone>(
+    mut recorded: ResMut<RecordedEvents<E>>,
+    mut events: EventReader<E>,
+) {
+    recorded.0.extend(events.read().cloned());
+}
+
+pub fn shipped_recipes() -> AspectRecipes {
+    let bytes = fs::read(PATH_TO_RECIPES).expect("Should have been able to read the recipes");
+    match AspectRecipes::from_ron(&bytes) {
+        Ok(r) => r,
+        Err(err) => panic!("The shipped recipes are invalid, {err}"),
+    }
+}
+
+pub fn shipped_rules() -> EndingRules {
+    let bytes = fs::read(PATH_TO_ENDING_RULES).expect("Should have been able to read the rules");
+    match EndingRules::from_ron(&bytes) {
+        Ok(r) => r,
+        Err(err) => panic!("The shipped ending rules are invalid, {err}"),
+    }
+}
+
+/// Only the paths of portraits and blips, there is no asset server to load them.
+pub fn shipped_characters() -> Characters {
+    let bytes = fs::read(PATH_TO_CHARACTERS).expect("Should have been able to read the characters");
+    match Characters::from_ron(&bytes) {
+        Ok(r) => r,
+        Err(err) => panic!("The shipped characters are invalid, {err}"),
+    }
+}
+
+pub fn shipped_strings(language: Language) -> StringTable {
+    let path = format!("{}/{}.strings.ron", PATH_TO_LOCALE, language.code());
+    let bytes = fs::read(&path).expect("Should have been able to read the string table");
+    match StringTable::from_ron(&bytes) {
+        Ok(r) => r,
+        Err(err) => panic!("{} is invalid, {}", path, err),
+    }
+}
+
+/// The strings of `language` with English as the fallback, there is no font.
+pub fn shipped_locale(language: Language) -> Locale {
+    Locale::new(
+        language,
+        shipped_strings(language),
+        shipped_strings(Language::English),
+    )
+}
+
+/// World position of the given level grid cell, the same way LDtk entities get placed.
+pub fn grid_position(x: i32, y: i32) -> Vec2 {
+    Vec2::new(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE)
+}
+
+pub struct TestApp {
+    app: App,
+}
+
+impl TestApp {
+    /// The bare app, game plugins get added with `with_plugins`.
+    /// Assets are placeholders, only the recipes and ending rules are read from disk.
+    pub fn headless() -> Self {
+        let recipes = shipped_recipes();
+        let ending_rules = shipped_rules();
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, AssetPlugin::default()))
+            .init_state::<GameState>()
+            .add_sub_state::<PauseState>()
+            .init_resource::<GameAssets>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<CurrentLevel>()
+            .init_resource::<Locale>()
+            .init_resource::<TutorialProgress>()
+            .init_resource::<TimeSinceGaming>()
+            .init_resource::<DialogueVariables>()
+            .insert_resource(LevelSelection::index(0))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                FRAME_TIME,
+            )))
+            // Plugins only init the recipes and rules, so this is what they are going to use.
+            .insert_resource(recipes)
+            .insert_resource(ending_rules)
+            // Sent by plugins that aren't part of the harness, the game systems still listen for them.
+            .add_event::<PlaySound>()
+            .add_event::<DialogueCompleteEvent>()
+            .add_event::<GameLoaded>()
+            .add_event::<LevelEntered>()
+            .add_event::<LevelEvent>()
+            .add_event::<FadeScreen>()
+            .add_event::<ScreenFaded>()
+            .add_event::<RestartRun>();
+        Self { app }
+    }
+
+    pub fn with_plugins<M>(mut self, plugins: impl Plugins<M>) -> Self {
+        self.app.add_plugins(plugins);
+        self
+    }
+
+    /// Keep track of all events of type `E`, they can be checked with `events`.
+    pub fn record_events<E: Event + Clone>(mut self) -> Self {
+        self.app
+            .insert_resource(RecordedEvents::<E>(Vec::new()))
+            .add_systems(Last, record_events::<E>);
+        self
+    }
+
+    /// Record every frame of player input, read it back with `recorded_input`.
+    pub fn recording(mut self, seed: u64) -> Self {
+        self.app.add_plugins(ReplayPlugin);
+        set_replay_mode(
+            &mut self.app,
+            ReplayMode::Recording {
+                file: String::new(),
+                recording: InputRecording::new(seed),
+            },
+        );
+        self
+    }
+
+    /// Feed `recording` into the app frame by frame, instead of what `press` injects.
+    pub fn replaying(mut self, recording: InputRecording) -> Self {
+        self.app.add_plugins(ReplayPlugin);
+        set_replay_mode(&mut self.app, ReplayMode::replaying(recording));
+        self
+    }
+
+    pub fn recorded_input(&self) -> Option<&InputRecording> {
+        match self.app.world().resource::<ReplayMode>() {
+            ReplayMode::Recording { recording, .. } => Some(recording),
+            _ => None,
+        }
+    }
+
+    pub fn events<E: Event + Clone>(&self) -> &[E] {
+        &self.app.world().resource::<RecordedEvents<E>>().0
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world().resource::<R>()
+    }
+
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) {
+        self.app.world_mut().insert_resource(resource);
+    }
+
+    /// The aspects of the sockets in one row, from left to right.
+    pub fn socket_aspects(&mut self, on_top: bool) -> Vec<Aspect> {
+        let world = self.app.world_mut();
+        let mut query = world.query::<(&Transform, &Socket)>();
+        let mut sockets = query
+            .iter(world)
+            .filter(|(_, socket)| socket.on_top == on_top)
+            .map(|(transform, socket)| (transform.translation.x, socket.aspect))
+            .collect::<Vec<(f32, Aspect)>>();
+        sockets.sort_by(|(x, _), (y, _)| x.total_cmp(y));
+        sockets.into_iter().map(|(_, aspect)| aspect).collect()
+    }
+
+    /// The names shown on the sockets in one row, from left to right.
+    /// Each socket has two, the name and its shadow.
+    pub fn socket_names(&mut self, on_top: bool) -> Vec<String> {
+        let world = self.app.world_mut();
+        let mut q_sockets = world.query::<(&Transform, &Socket, &Children)>();
+        let mut q_texts = world.query::<&Text>();
+        let world = self.app.world();
+        let mut sockets = q_sockets
+            .iter(world)
+            .filter(|(_, socket, _)| socket.on_top == on_top)
+            .collect::<Vec<_>>();
+        sockets.sort_by(|(x, _, _), (y, _, _)| x.translation.x.total_cmp(&y.translation.x));
+        sockets
+            .into_iter()
+            .flat_map(|(_, _, children)| children.iter())
+            .filter_map(|child| q_texts.get(world, *child).ok())
+            .map(|text| text.sections[0].value.clone())
+            .collect()
+    }
+
+    pub fn bed_position(&mut self) -> Option<Vec2> {
+        let world = self.app.world_mut();
+        let mut query = world.query_filtered::<&Transform, With<Bed>>();
+        query
+            .iter(world)
+            .next()
+            .map(|transform| transform.translation.truncate())
+    }
+
+    pub fn update(&mut self) {
+        self.app.update();
+    }
+
+    /// Run frames until `seconds` have passed in game time.
+    pub fn wait(&mut self, seconds: f32) {
+        for _ in 0..(seconds / FRAME_TIME).ceil() as usize {
+            self.update();
+        }
+    }
+
+    /// Switch to `state` and run the frame in which the transition happens.
+    pub fn enter_state(&mut self, state: GameState) {
+        self.app
+            .world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(state);
+        self.update();
+    }
+
+    /// Run a single frame with the given input, as if it was pressed this frame.
+    pub fn press(&mut self, input: impl FnOnce(&mut PlayerInput)) {
+        input(&mut self.app.world_mut().resource_mut::<PlayerInput>());
+        self.update();
+        *self.app.world_mut().resource_mut::<PlayerInput>() = PlayerInput::default();
+    }
+
+    pub fn send_event<E: Event>(&mut self, event: E) {
+        self.app.world_mut().send_event(event);
+    }
+
+    pub fn spawn_player(&mut self, pos: Vec2) -> Entity {
+        self.app
+            .world_mut()
+            .spawn((
+                Player {
+                    can_move: true,
+                    x_value_tutorial_dialogue: 0.0,
+                },
+                Transform::from_translation((pos - PLAYER_PIVOT).extend(0.0)),
+            ))
+            .id()
+    }
+
+    /// Where the player's sprite is, its feet are `PLAYER_PIVOT` below that.
+    pub fn player_position(&mut self) -> Vec2 {
+        let world = self.app.world_mut();
+        let mut query = world.query_filtered::<&Transform, With<Player>>();
+        query.single(world).translation.truncate()
+    }
+
+    /// Teleport the player so that its feet are on `pos` and run a frame for highlights to catch up.
+    pub fn move_player_to(&mut self, pos: Vec2) {
+        let world = self.app.world_mut();
+        let mut query = world.query_filtered::<&mut Transform, With<Player>>();
+        for mut transform in query.iter_mut(world) {
+            transform.translation = (pos - PLAYER_PIVOT).extend(0.0);
+        }
+        self.update();
+    }
+
+    pub fn spawn_socket(&mut self, aspect: Aspect, on_top: bool, x: i32, y: i32) {
+        self.app.world_mut().spawn((
+            AspectSocketInitiater::new(aspect, on_top),
+            GridCoords::new(x, y),
+        ));
+    }
+
+    pub fn spawn_combiner(&mut self, x: i32, y: i32) {
+        self.app
+            .world_mut()
+            .spawn((AspectCombinerInitiater, GridCoords::new(x, y)));
+    }
+
+    /// The exit is level scoped, leaving the level despawns it like LDtk would.
+    pub fn spawn_level_exit(&mut self, level: &str, entrance: &str, x: i32, y: i32) {
+        self.app.world_mut().spawn((
+            LevelScoped,
+            LevelExit::new(level, entrance),
+            GridCoords::new(x, y),
+        ));
+    }
+
+    /// The entrance is level scoped, just like the exits.
+    pub fn spawn_level_entrance(&mut self, name: &str, x: i32, y: i32) {
+        self.app.world_mut().spawn((
+            LevelScoped,
+            LevelEntrance {
+                name: name.to_string(),
+            },
+            GridCoords::new(x, y),
+        ));
+    }
+}
@@ -0,0 +1,270 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::{LevelEvent, LevelIid};
+use bevy_yarnspinner::events::DialogueCompleteEvent;
+
+use crate::{
+    aspect::{Aspect, AspectPlugin, CombinedAspect, Combiner},
+    locale::Language,
+    npc::narrator::{NarratorPlugin, TriggeredNarratorDialogue},
+    player::input::PlayerInput,
+    ui::ScreenFaded,
+    world::{CurrentLevel, LevelPlugin, MapBedPlugin, PlayerWentToBed},
+    GameState,
+};
+
+use super::{grid_position, shipped_locale, TestApp};
+
+const JOY: (i32, i32) = (0, 4);
+const SADNESS: (i32, i32) = (0, 0);
+const COMBINER: (i32, i32) = (5, 2);
+const EXIT: (i32, i32) = (12, 2);
+const ENTRANCE: (i32, i32) = (10, 2);
+/// A bit more than the narrator waits before the ending starts.
+const ENDING_DELAY: f32 = 2.5;
+
+/// Joy on top and sadness at the bottom, with one empty socket in each row for the result.
+fn combiner_room() -> TestApp {
+    room_with(Aspect::Sadness)
+}
+
+/// Joy on top and `bottom` below it, the bottom socket sits where sadness does in `combiner_room`.
+fn room_with(bottom: Aspect) -> TestApp {
+    let mut app = TestApp::headless()
+        .with_plugins((AspectPlugin, MapBedPlugin, NarratorPlugin))
+        .record_events::<CombinedAspect>()
+        .record_events::<PlayerWentToBed>()
+        .record_events::<TriggeredNarratorDialogue>();
+    app.enter_state(GameState::Gaming);
+
+    app.spawn_socket(Aspect::Joy, true, JOY.0, JOY.1);
+    app.spawn_socket(Aspect::NotImplemented, true, 2, 4);
+    app.spawn_socket(bottom, false, SADNESS.0, SADNESS.1);
+    app.spawn_socket(Aspect::NotImplemented, false, 2, 0);
+    app.spawn_combiner(COMBINER.0, COMBINER.1);
+    app.spawn_player(grid_position(10, 10));
+    app.update();
+    app
+}
+
+fn select(app: &mut TestApp, pos: Vec2) {
+    app.move_player_to(pos);
+    app.press(|input| input.select_socket = true);
+}
+
+/// Combine the aspects of the first sockets, joy and sadness in `combiner_room`.
+fn combine_joy_and_sadness(app: &mut TestApp) {
+    select(app, grid_position(JOY.0, JOY.1));
+    select(app, grid_position(SADNESS.0, SADNESS.1));
+    select(app, grid_position(COMBINER.0, COMBINER.1));
+    // The result gets pushed into the sockets once the event is read.
+    app.update();
+}
+
+/// Go to bed once the bed showed up and play the run through to the narrator's ending.
+fn finish_run(app: &mut TestApp) -> String {
+    // Stands in for the dialogue that plays after every combination.
+    app.send_event(DialogueCompleteEvent {
+        source: Entity::PLACEHOLDER,
+    });
+    app.update();
+    let bed = app
+        .bed_position()
+        .expect("The bed should appear once all sockets are full");
+    select(app, bed);
+    assert_eq!(app.events::<PlayerWentToBed>().len(), 1);
+
+    // Ima's final dialogue ends with `<<trigger_ending>>`, which does the same.
+    app.enter_state(GameState::Ending);
+    app.wait(ENDING_DELAY);
+    match app.events::<TriggeredNarratorDialogue>() {
+        [TriggeredNarratorDialogue(dialogue)] => dialogue.node().to_string(),
+        events => panic!("The narrator should play one ending, got {}", events.len()),
+    }
+}
+
+#[test]
+fn validate_combination_fills_sockets() {
+    let mut app = combiner_room();
+    combine_joy_and_sadness(&mut app);
+
+    assert_eq!(
+        app.events::<CombinedAspect>(),
+        &[CombinedAspect {
+            left: Aspect::Joy,
+            right: Aspect::Sadness,
+            result: Aspect::Nostalgia,
+        }]
+    );
+    assert_eq!(
+        app.resource::<Combiner>().combined_aspects,
+        vec![Aspect::Nostalgia]
+    );
+    assert_eq!(
+        app.socket_aspects(true),
+        vec![Aspect::Joy, Aspect::Nostalgia]
+    );
+    assert_eq!(
+        app.socket_aspects(false),
+        vec![Aspect::Sadness, Aspect::Nostalgia]
+    );
+}
+
+#[test]
+fn validate_undo_empties_sockets() {
+    let mut app = combiner_room();
+    combine_joy_and_sadness(&mut app);
+    app.press(|input| input.undo_combination = true);
+
+    assert!(app.resource::<Combiner>().combined_aspects.is_empty());
+    assert_eq!(
+        app.socket_aspects(true),
+        vec![Aspect::Joy, Aspect::NotImplemented]
+    );
+    assert_eq!(
+        app.socket_aspects(false),
+        vec![Aspect::Sadness, Aspect::NotImplemented]
+    );
+}
+
+#[test]
+fn validate_bed_appears_after_last_combination() {
+    let mut app = combiner_room();
+    combine_joy_and_sadness(&mut app);
+    assert!(app.resource::<Combiner>().all_sockets_full);
+    assert!(app.bed_position().is_none());
+
+    // Stands in for the dialogue that plays after every combination.
+    app.send_event(DialogueCompleteEvent {
+        source: Entity::PLACEHOLDER,
+    });
+    app.update();
+    let bed = app
+        .bed_position()
+        .expect("The bed should appear once all sockets are full");
+
+    select(&mut app, bed);
+    assert_eq!(app.events::<PlayerWentToBed>().len(), 1);
+}
+
+#[test]
+fn validate_run_ends_with_good_ending() {
+    let mut app = combiner_room();
+    combine_joy_and_sadness(&mut app);
+
+    // Joy, nostalgia twice and sadness score 6, which is neither too positive nor too negative.
+    assert_eq!(finish_run(&mut app), "GoodEnding");
+}
+
+#[test]
+fn validate_run_ends_with_too_positive_ending() {
+    let mut app = room_with(Aspect::Nostalgia);
+    combine_joy_and_sadness(&mut app);
+    assert_eq!(
+        app.resource::<Combiner>().combined_aspects,
+        vec![Aspect::Motivation]
+    );
+
+    // Joy, nostalgia and motivation twice score 10.
+    assert_eq!(finish_run(&mut app), "BadEndingTooPositive");
+}
+
+#[test]
+fn validate_socket_names_follow_language() {
+    let mut app = combiner_room();
+    let german = shipped_locale(Language::German);
+    let names = |aspects: [Aspect; 2]| {
+        aspects
+            .iter()
+            .flat_map(|aspect| [german.aspect_name(*aspect), german.aspect_name(*aspect)])
+            .collect::<Vec<String>>()
+    };
+    let top = names([Aspect::Joy, Aspect::NotImplemented]);
+    let bottom = names([Aspect::Sadness, Aspect::NotImplemented]);
+
+    app.insert_resource(german);
+    app.update();
+    assert_eq!(app.socket_names(true), top);
+    assert_eq!(app.socket_names(false), bottom);
+}
+
+/// The sockets of `combiner_room` as they are in LDtk, with a way out to `Level_1`.
+fn spawn_first_level(app: &mut TestApp) {
+    app.spawn_socket(Aspect::Joy, true, JOY.0, JOY.1);
+    app.spawn_socket(Aspect::NotImplemented, true, 2, 4);
+    app.spawn_socket(Aspect::Sadness, false, SADNESS.0, SADNESS.1);
+    app.spawn_socket(Aspect::NotImplemented, false, 2, 0);
+    app.spawn_combiner(COMBINER.0, COMBINER.1);
+    app.spawn_level_exit("Level_1", "west", EXIT.0, EXIT.1);
+    app.spawn_level_entrance("east", ENTRANCE.0, ENTRANCE.1);
+}
+
+/// Walk into the exit at `exit` and let the screen fade to black, which swaps out the level.
+fn leave_level(app: &mut TestApp, exit: (i32, i32)) {
+    app.move_player_to(grid_position(exit.0, exit.1));
+    app.send_event(ScreenFaded { to_black: true });
+    app.update();
+}
+
+/// Stands in for LDtk being done with spawning the level, the player gets placed at the entrance.
+fn arrive_in_level(app: &mut TestApp, level: &str) {
+    app.send_event(LevelEvent::Transformed(LevelIid::new(level)));
+    app.update();
+}
+
+#[test]
+fn validate_level_exit_moves_to_entrance_and_back() {
+    let mut app = TestApp::headless().with_plugins((AspectPlugin, LevelPlugin));
+    app.enter_state(GameState::Gaming);
+    spawn_first_level(&mut app);
+    app.spawn_player(grid_position(ENTRANCE.0, ENTRANCE.1));
+    app.update();
+    combine_joy_and_sadness(&mut app);
+    assert_eq!(
+        app.socket_aspects(true),
+        vec![Aspect::Joy, Aspect::Nostalgia]
+    );
+
+    leave_level(&mut app, EXIT);
+    assert_eq!(app.resource::<CurrentLevel>().0, "Level_1");
+    assert!(app.socket_aspects(true).is_empty());
+    assert!(app.socket_aspects(false).is_empty());
+
+    app.spawn_level_exit("Level_0", "east", 1, 6);
+    app.spawn_level_entrance("west", 3, 6);
+    arrive_in_level(&mut app, "Level_1");
+    assert_eq!(app.player_position(), grid_position(3, 6));
+
+    leave_level(&mut app, (1, 6));
+    assert_eq!(app.resource::<CurrentLevel>().0, "Level_0");
+    spawn_first_level(&mut app);
+    arrive_in_level(&mut app, "Level_0");
+    assert_eq!(app.player_position(), grid_position(ENTRANCE.0, ENTRANCE.1));
+    assert_eq!(
+        app.socket_aspects(true),
+        vec![Aspect::Joy, Aspect::Nostalgia]
+    );
+    assert_eq!(
+        app.socket_aspects(false),
+        vec![Aspect::Sadness, Aspect::Nostalgia]
+    );
+}
+
+#[test]
+fn validate_replay_feeds_recorded_input() {
+    let mut app = combiner_room().recording(42);
+    combine_joy_and_sadness(&mut app);
+    let recording = app
+        .recorded_input()
+        .expect("The app should be recording")
+        .clone();
+    assert!(recording.frames.iter().any(|input| input.select_socket));
+
+    // Replays can't teleport the player, so only check that the input arrives in the same frames.
+    let mut replay = TestApp::headless().replaying(recording.clone());
+    let mut replayed = Vec::new();
+    for _ in 0..recording.frames.len() {
+        replay.update();
+        replayed.push(replay.resource::<PlayerInput>().clone());
+    }
+    assert_eq!(replayed, recording.frames);
+}
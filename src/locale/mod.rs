@@ -0,0 +1,280 @@
+//! String tables for the UI and the language setting.
+//! The dialogue itself is translated through yarn, see `dialogue/<code>.strings.csv`.
+
+#[cfg(test)]
+mod test;
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use bevy_yarnspinner::prelude::Localizations;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::{aspect::Aspect, utils::storage, GameAssets, GameState};
+
+const CONFIG_FILE: &str = "language.ron";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, EnumIter, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    /// The locale code, string tables are named after it.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en-US",
+            Language::German => "de-DE",
+        }
+    }
+
+    /// Always in the language itself, so that players can find their own.
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    /// The language that comes after this one in the settings, wraps around.
+    pub fn next(&self, step: i32) -> Self {
+        let languages = Language::iter().collect::<Vec<Language>>();
+        let index = languages.iter().position(|l| l == self).unwrap_or_default() as i32;
+        languages[(index + step).rem_euclid(languages.len() as i32) as usize]
+    }
+
+    fn strings(&self, assets: &GameAssets) -> Handle<StringTable> {
+        match self {
+            Language::English => assets.english_strings.clone(),
+            Language::German => assets.german_strings.clone(),
+        }
+    }
+}
+
+/// Yarn lines are written in English, every other language is a translation of them.
+pub fn yarn_localizations() -> Localizations {
+    Localizations {
+        base_localization: Language::English.code().into(),
+        translations: Language::iter()
+            .filter(|language| *language != Language::English)
+            .map(|language| language.code().into())
+            .collect(),
+    }
+}
+
+/// The language picked in the settings, stored next to the other configs.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LanguageSetting {
+    pub language: Language,
+}
+
+impl LanguageSetting {
+    pub fn load() -> Self {
+        let contents = match storage::read(CONFIG_FILE) {
+            Some(r) => r,
+            None => return Self::default(),
+        };
+
+        match ron::from_str::<Self>(&contents) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to parse language config, using defaults, {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to serialize language config, {}", err);
+                return;
+            }
+        };
+        if let Err(err) = storage::write(CONFIG_FILE, &contents) {
+            error!("failed to write language config, {}", err);
+        }
+    }
+}
+
+/// UI strings of a single language, by key.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct StringTable {
+    strings: HashMap<String, String>,
+}
+
+impl StringTable {
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, ron::error::SpannedError> {
+        Ok(Self {
+            strings: ron::de::from_bytes(bytes)?,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(|s| s.as_str())
+    }
+
+    #[cfg(test)]
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.strings.keys()
+    }
+}
+
+#[derive(Default)]
+struct StringTableLoader;
+
+impl AssetLoader for StringTableLoader {
+    type Asset = StringTable;
+    type Settings = ();
+    type Error = ron::error::SpannedError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        StringTable::from_ron(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["strings.ron"]
+    }
+}
+
+/// Everything the UI needs to show text in the current language.
+#[derive(Resource, Default)]
+pub struct Locale {
+    language: Language,
+    strings: StringTable,
+    /// English, for keys that are missing in the current language.
+    fallback: StringTable,
+    font: Handle<Font>,
+}
+
+impl Locale {
+    /// A locale without a font, used to switch languages in tests.
+    #[cfg(test)]
+    pub fn new(language: Language, strings: StringTable, fallback: StringTable) -> Self {
+        Self {
+            language,
+            strings,
+            fallback,
+            font: Handle::default(),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn font(&self) -> Handle<Font> {
+        self.font.clone()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).or_else(|| self.fallback.get(key))
+    }
+
+    /// The string for `key`, the key itself if no language has it.
+    pub fn text(&self, key: &str) -> String {
+        self.get(key).unwrap_or(key).to_string()
+    }
+
+    pub fn aspect_name(&self, aspect: Aspect) -> String {
+        self.text(&format!("aspect.{}", aspect))
+    }
+}
+
+/// Texts with this get their string swapped out whenever the language changes.
+#[derive(Component)]
+pub struct LocalizedText(pub &'static str);
+
+fn string_table(
+    assets: &GameAssets,
+    tables: &Assets<StringTable>,
+    language: Language,
+) -> StringTable {
+    match tables.get(&language.strings(assets)) {
+        Some(r) => r.clone(),
+        None => {
+            error!("strings for {} are not loaded", language.code());
+            StringTable::default()
+        }
+    }
+}
+
+fn insert_locale(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    setting: Res<LanguageSetting>,
+    tables: Res<Assets<StringTable>>,
+) {
+    commands.insert_resource(Locale {
+        language: setting.language,
+        strings: string_table(&assets, &tables, setting.language),
+        fallback: string_table(&assets, &tables, Language::English),
+        // Silver has the glyphs of every shipped language, `validate_font_covers_languages`
+        // makes sure it stays that way when strings get added.
+        font: assets.silver_font.clone(),
+    });
+}
+
+fn reload_locale(
+    commands: Commands,
+    assets: Res<GameAssets>,
+    setting: Res<LanguageSetting>,
+    tables: Res<Assets<StringTable>>,
+    mut ev_asset: EventReader<AssetEvent<StringTable>>,
+) {
+    if ev_asset
+        .read()
+        .any(|ev| matches!(ev, AssetEvent::Modified { .. }))
+    {
+        info!("reloaded string tables");
+        insert_locale(commands, assets, setting, tables);
+    }
+}
+
+/// Localized texts get the string and font of the current language.
+/// Other texts pick up the font when they are spawned or, like key labels, when they update.
+fn relocalize_texts(locale: Res<Locale>, mut q_texts: Query<(&mut Text, &LocalizedText)>) {
+    for (mut text, localized) in &mut q_texts {
+        for section in &mut text.sections {
+            section.style.font = locale.font();
+        }
+        text.sections[0].value = locale.text(localized.0);
+    }
+}
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<StringTable>()
+            .init_asset_loader::<StringTableLoader>()
+            .init_resource::<Locale>()
+            .insert_resource(LanguageSetting::load())
+            .add_systems(OnExit(GameState::AssetLoading), insert_locale)
+            .add_systems(
+                Update,
+                (
+                    insert_locale.run_if(resource_changed::<LanguageSetting>),
+                    reload_locale,
+                    relocalize_texts.run_if(resource_changed::<Locale>),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
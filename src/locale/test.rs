@@ -0,0 +1,182 @@
+use std::{collections::HashSet, fs};
+
+use ab_glyph::{Font, FontRef};
+use strum::IntoEnumIterator;
+
+use crate::{aspect::Aspect, harness::shipped_strings, player::input_map::InputAction};
+
+use super::Language;
+
+const PATH_TO_FONT: &str = "assets/fonts/Silver.ttf";
+const PATH_TO_DIALOGUE: &str = "assets/dialogue";
+
+/// The dialogue lines of all yarn files, without commands and node headers.
+fn dialogue_lines() -> Vec<String> {
+    let mut lines = Vec::new();
+    for entry in fs::read_dir(PATH_TO_DIALOGUE).expect("Can't read entries in dialogue dir") {
+        let path = entry.expect("Can't get entry in dialogue dir").path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("yarn") {
+            continue;
+        }
+
+        let contents = fs::read_to_string(path).expect("Should have been able to read the file");
+        let mut in_body = false;
+        for line in contents.lines().map(str::trim) {
+            match line {
+                "---" => in_body = true,
+                "===" => in_body = false,
+                _ if !in_body || line.is_empty() || line.starts_with("<<") => {}
+                _ => lines.push(line.to_string()),
+            }
+        }
+    }
+    lines
+}
+
+fn line_id(line: &str) -> Option<&str> {
+    line.split_whitespace()
+        .find_map(|tag| tag.strip_prefix("#line:"))
+}
+
+/// The fields of a single csv record, quoted fields may contain commas and `""` for quotes.
+fn csv_fields(record: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// The translated dialogue of `language`, the text column of its strings csv.
+fn translated_lines(language: Language) -> Vec<String> {
+    let path = format!("{}/{}.strings.csv", PATH_TO_DIALOGUE, language.code());
+    let contents = fs::read_to_string(&path).expect("Should have been able to read the strings");
+    contents
+        .lines()
+        .skip(1)
+        .filter(|r| !r.is_empty())
+        .filter_map(|record| csv_fields(record).get(2).cloned())
+        .collect()
+}
+
+#[test]
+fn validate_font_covers_languages() {
+    let bytes = fs::read(PATH_TO_FONT).expect("Should have been able to read the font");
+    let font = FontRef::try_from_slice(&bytes).expect("The shipped font is invalid");
+
+    for language in Language::iter() {
+        let table = shipped_strings(language);
+        let mut texts = table
+            .keys()
+            .filter_map(|key| table.get(key))
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+        if language == Language::English {
+            texts.extend(dialogue_lines());
+        } else {
+            texts.extend(translated_lines(language));
+        }
+
+        for text in &texts {
+            // Glyph 0 is the box fonts draw for characters they don't have.
+            if let Some(c) = text
+                .chars()
+                .find(|c| !c.is_control() && font.glyph_id(*c).0 == 0)
+            {
+                panic!(
+                    "{} has no glyph for '{}' in '{}' ({})",
+                    PATH_TO_FONT,
+                    c,
+                    text,
+                    language.code()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn validate_dialogue_line_ids() {
+    let mut ids = HashSet::new();
+    for line in dialogue_lines() {
+        let id = line_id(&line).unwrap_or_else(|| panic!("'{}' has no line id", line));
+        assert!(ids.insert(id.to_string()), "line id '{}' is not unique", id);
+    }
+}
+
+#[test]
+fn validate_dialogue_translations() {
+    let ids = dialogue_lines()
+        .iter()
+        .filter_map(|line| line_id(line).map(|id| format!("line:{}", id)))
+        .collect::<HashSet<String>>();
+
+    for language in Language::iter().filter(|l| *l != Language::English) {
+        let path = format!("{}/{}.strings.csv", PATH_TO_DIALOGUE, language.code());
+        let contents =
+            fs::read_to_string(&path).expect("Should have been able to read the strings");
+
+        let mut translated = HashSet::new();
+        for record in contents.lines().skip(1).filter(|r| !r.is_empty()) {
+            let fields = csv_fields(record);
+            assert!(
+                fields.len() >= 3,
+                "malformed record in {}, '{}'",
+                path,
+                record
+            );
+            assert!(
+                !fields[2].trim().is_empty(),
+                "{} has no translation in {}",
+                fields[1],
+                path
+            );
+            translated.insert(fields[1].clone());
+        }
+
+        for id in &ids {
+            assert!(translated.contains(id), "{} is missing in {}", id, path);
+        }
+    }
+}
+
+#[test]
+fn validate_string_tables_complete() {
+    let english = shipped_strings(Language::English);
+    for language in Language::iter() {
+        let table = shipped_strings(language);
+        for key in english.keys() {
+            let value = table
+                .get(key)
+                .unwrap_or_else(|| panic!("{} is missing in {}", key, language.code()));
+            assert!(!value.is_empty(), "{} is empty in {}", key, language.code());
+        }
+    }
+}
+
+#[test]
+fn validate_string_keys_of_enums() {
+    let english = shipped_strings(Language::English);
+    let mut keys = Vec::new();
+    keys.extend(Aspect::iter().map(|aspect| format!("aspect.{}", aspect)));
+    keys.extend(
+        Aspect::iter()
+            .filter(|aspect| *aspect != Aspect::NotImplemented)
+            .map(|aspect| format!("codex.description.{}", aspect)),
+    );
+    keys.extend(InputAction::iter().map(|action| format!("action.{}", action)));
+
+    for key in keys {
+        assert!(english.get(&key).is_some(), "{} is missing", key);
+    }
+}
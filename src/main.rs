@@ -3,19 +3,33 @@
 mod aspect;
 mod assets;
 mod audio;
+#[cfg(test)]
+mod harness;
+mod locale;
 mod npc;
 mod player;
+mod run;
+mod save;
 mod ui;
 mod utils;
 mod world;
 
 pub use assets::GameAssets;
-pub type GameRng = rand_xoshiro::Xoshiro256PlusPlus;
+/// The rng for everything that should play out the same when a recording gets replayed.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng(pub rand_xoshiro::Xoshiro256PlusPlus);
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(rand_xoshiro::Xoshiro256PlusPlus::seed_from_u64(seed))
+    }
+}
 
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
 use bevy::window::{PresentMode, Window, WindowMode, WindowResolution};
 use bevy_yarnspinner::prelude::*;
+use rand::SeedableRng;
 
 use bevy_asset_loader::prelude::*;
 use bevy_particle_systems::ParticleSystemPlugin;
@@ -29,12 +43,22 @@ const BACKGROUND_COLOR: Color = Color::srgb(0.0, 0.0, 0.0);
 pub enum GameState {
     #[default]
     AssetLoading,
+    Title,
     Intro,
     Gaming,
     Ending,
     GameOver,
 }
 
+/// Exists in every state after the title screen, the game can be paused in all of them.
+#[derive(SubStates, Clone, Copy, Eq, PartialEq, Debug, Hash, Default)]
+#[source(GameState = GameState::Intro | GameState::Gaming | GameState::Ending | GameState::GameOver)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
 fn main() {
     App::new()
         .add_plugins((
@@ -42,6 +66,8 @@ fn main() {
                 .set(ImagePlugin::default_nearest())
                 .set(AssetPlugin {
                     meta_check: AssetMetaCheck::Never,
+                    // Hot reload data assets like the aspect recipes in dev builds.
+                    watch_for_changes_override: Some(cfg!(debug_assertions)),
                     ..default()
                 })
                 .set(WindowPlugin {
@@ -68,13 +94,15 @@ fn main() {
                 YarnFileSource::file("dialogue/aspects.yarn"),
                 YarnFileSource::file("dialogue/others.yarn"),
             ])
+            .with_localizations(locale::yarn_localizations())
             .with_development_file_generation(DevelopmentFileGeneration::None),
         ))
         .insert_resource(Msaa::Off)
         .init_state::<GameState>()
+        .add_sub_state::<PauseState>()
         .add_loading_state(
             LoadingState::new(GameState::AssetLoading)
-                .continue_to_state(GameState::Intro)
+                .continue_to_state(GameState::Title)
                 .load_collection::<GameAssets>(),
         )
         .insert_resource(ClearColor(BACKGROUND_COLOR))
@@ -86,6 +114,9 @@ fn main() {
             aspect::AspectPlugin,
             ui::UiPlugin,
             npc::NpcPlugin,
+            save::SavePlugin,
+            run::RunPlugin,
+            locale::LocalePlugin,
         ))
         .run();
 }
@@ -0,0 +1,403 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::texture::TRANSPARENT_IMAGE_HANDLE,
+};
+use bevy_kira_audio::AudioSource;
+use bevy_trickfilm::prelude::*;
+use serde::Deserialize;
+
+use crate::{GameAssets, GameState};
+
+/// The typing speed of the narrator if there are no characters loaded.
+const DEFAULT_TYPING_SPEED: f32 = 20.0;
+
+fn default_playback_rate() -> f64 {
+    1.0
+}
+
+fn default_tiles() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+struct RawExpression {
+    image: String,
+    /// Size of a single frame, the image is a sprite sheet if this is given.
+    tile_size: Option<u32>,
+    #[serde(default = "default_tiles")]
+    columns: u32,
+    #[serde(default = "default_tiles")]
+    rows: u32,
+    animation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawProfile {
+    #[serde(default)]
+    name: String,
+    display_name: Option<String>,
+    portrait: Option<String>,
+    blip: Option<String>,
+    #[serde(default = "default_playback_rate")]
+    playback_rate: f64,
+    #[serde(default)]
+    pitch_range: f64,
+    typing_speed: f32,
+    #[serde(default)]
+    punctuation_pauses: HashMap<String, f32>,
+    #[serde(default)]
+    expressions: HashMap<String, RawExpression>,
+}
+
+#[derive(Deserialize)]
+struct RawCharacters {
+    narrator: RawProfile,
+    #[serde(default)]
+    fallback: Option<String>,
+    characters: Vec<RawProfile>,
+}
+
+/// A portrait that replaces the neutral one for lines with the matching yarn tag, e.g. `#smile`.
+#[derive(Clone, Debug)]
+pub struct Expression {
+    pub image_path: String,
+    /// A trickfilm clip, e.g. `ui/portraits/ami.trickfilm#smile`.
+    pub animation_path: Option<String>,
+    pub image: Handle<Image>,
+    /// Only sprite sheets have a layout, the portrait shows their first frame if there is no animation.
+    pub layout: Option<Handle<TextureAtlasLayout>>,
+    pub animation: Option<Handle<AnimationClip2D>>,
+    tile_size: Option<u32>,
+    columns: u32,
+    rows: u32,
+}
+
+impl Expression {
+    fn new(character: &str, tag: &str, raw: RawExpression) -> Result<Self, CharacterError> {
+        if raw.animation.is_some() && raw.tile_size.is_none() {
+            return Err(CharacterError::AnimationWithoutTiles(format!(
+                "{}#{}",
+                character, tag
+            )));
+        }
+        Ok(Self {
+            image_path: raw.image,
+            animation_path: raw.animation,
+            image: Handle::default(),
+            layout: None,
+            animation: None,
+            tile_size: raw.tile_size,
+            columns: raw.columns,
+            rows: raw.rows,
+        })
+    }
+}
+
+/// How a speaker looks and sounds in dialogue.
+#[derive(Clone, Debug)]
+pub struct CharacterProfile {
+    /// The character name in the yarn files, e.g. `Ami: Hello.`
+    pub name: String,
+    /// The name shown above the dialogue.
+    pub display_name: String,
+    pub portrait_path: Option<String>,
+    pub blip_path: Option<String>,
+    /// The neutral portrait, transparent if the speaker has none, like the narrator.
+    pub portrait: Handle<Image>,
+    /// By yarn tag, without the `#`.
+    pub expressions: HashMap<String, Expression>,
+    /// Speakers without a blip write silently.
+    pub blip: Option<Handle<AudioSource>>,
+    pub playback_rate: f64,
+    /// Random offset of the playback rate of each blip.
+    pub pitch_range: f64,
+    /// Graphemes per second.
+    pub typing_speed: f32,
+    /// Multipliers of the pause after a punctuation mark, marks that are missing use 1.0.
+    punctuation_pauses: HashMap<String, f32>,
+}
+
+impl CharacterProfile {
+    fn new(raw: RawProfile) -> Result<Self, CharacterError> {
+        if raw.typing_speed <= 0.0 {
+            return Err(CharacterError::InvalidSpeed(raw.name));
+        }
+        let mut expressions = HashMap::new();
+        for (tag, raw_expression) in raw.expressions {
+            let expression = Expression::new(&raw.name, &tag, raw_expression)?;
+            expressions.insert(tag, expression);
+        }
+        Ok(Self {
+            display_name: raw.display_name.unwrap_or_else(|| raw.name.clone()),
+            name: raw.name,
+            portrait_path: raw.portrait,
+            blip_path: raw.blip,
+            portrait: TRANSPARENT_IMAGE_HANDLE,
+            expressions,
+            blip: None,
+            playback_rate: raw.playback_rate,
+            pitch_range: raw.pitch_range,
+            typing_speed: raw.typing_speed,
+            punctuation_pauses: raw.punctuation_pauses,
+        })
+    }
+
+    pub fn punctuation_pause_multiplier(&self, mark: &str) -> f32 {
+        self.punctuation_pauses.get(mark).copied().unwrap_or(1.0)
+    }
+
+    /// The expression of the first tag that has one, `None` means the neutral portrait.
+    pub fn expression(&self, tags: &[String]) -> Option<&Expression> {
+        tags.iter().find_map(|tag| self.expressions.get(tag))
+    }
+}
+
+/// The narrator, if there are no characters loaded.
+impl Default for CharacterProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            display_name: String::new(),
+            portrait_path: None,
+            blip_path: None,
+            portrait: TRANSPARENT_IMAGE_HANDLE,
+            expressions: HashMap::new(),
+            blip: None,
+            playback_rate: default_playback_rate(),
+            pitch_range: 0.0,
+            typing_speed: DEFAULT_TYPING_SPEED,
+            punctuation_pauses: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CharacterError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    EmptyName,
+    /// Names starting with `_` are reserved for speakers the player doesn't know yet.
+    ReservedName(String),
+    DuplicateName(String),
+    InvalidSpeed(String),
+    AnimationWithoutTiles(String),
+    UnknownFallback(String),
+}
+
+impl fmt::Display for CharacterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharacterError::Io(err) => write!(f, "could not read characters, {}", err),
+            CharacterError::Ron(err) => write!(f, "could not parse characters, {}", err),
+            CharacterError::EmptyName => write!(f, "character without a name"),
+            CharacterError::ReservedName(name) => {
+                write!(f, "character names must not start with '_', '{}'", name)
+            }
+            CharacterError::DuplicateName(name) => {
+                write!(f, "character is defined twice, '{}'", name)
+            }
+            CharacterError::InvalidSpeed(name) => {
+                write!(f, "typing speed must be above 0, '{}'", name)
+            }
+            CharacterError::AnimationWithoutTiles(expression) => write!(
+                f,
+                "animated expression needs a tile size for its frames, '{}'",
+                expression
+            ),
+            CharacterError::UnknownFallback(name) => {
+                write!(f, "fallback is not one of the characters, '{}'", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CharacterError {}
+
+impl From<std::io::Error> for CharacterError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for CharacterError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Ron(err)
+    }
+}
+
+/// Everyone who speaks in the yarn files, the dialogue audio and typewriter both read from this.
+/// Same as the ending rules, this is both the asset and the resource.
+#[derive(Asset, Resource, TypePath, Clone, Debug, Default)]
+pub struct Characters {
+    narrator: CharacterProfile,
+    /// The name of the character that speakers without a profile sound and look like.
+    fallback: Option<String>,
+    characters: Vec<CharacterProfile>,
+}
+
+impl Characters {
+    /// Portraits and blips are only paths here, the loader turns them into handles.
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, CharacterError> {
+        let raw: RawCharacters = ron::de::from_bytes(bytes)?;
+        let mut characters: Vec<CharacterProfile> = Vec::new();
+        for raw_profile in raw.characters {
+            if raw_profile.name.is_empty() {
+                return Err(CharacterError::EmptyName);
+            }
+            if raw_profile.name.starts_with('_') {
+                return Err(CharacterError::ReservedName(raw_profile.name));
+            }
+            if characters.iter().any(|c| c.name == raw_profile.name) {
+                return Err(CharacterError::DuplicateName(raw_profile.name));
+            }
+            characters.push(CharacterProfile::new(raw_profile)?);
+        }
+        if let Some(fallback) = &raw.fallback {
+            if !characters.iter().any(|c| &c.name == fallback) {
+                return Err(CharacterError::UnknownFallback(fallback.clone()));
+            }
+        }
+
+        Ok(Self {
+            narrator: CharacterProfile::new(raw.narrator)?,
+            fallback: raw.fallback,
+            characters,
+        })
+    }
+
+    fn load_dependencies(&mut self, load_context: &mut LoadContext) {
+        for profile in self.characters.iter_mut().chain([&mut self.narrator]) {
+            if let Some(path) = &profile.portrait_path {
+                profile.portrait = load_context.load(path.as_str());
+            }
+            profile.blip = profile
+                .blip_path
+                .as_ref()
+                .map(|path| load_context.load(path.as_str()));
+
+            for (tag, expression) in profile.expressions.iter_mut() {
+                expression.image = load_context.load(expression.image_path.as_str());
+                expression.layout = expression.tile_size.map(|tile_size| {
+                    load_context.add_labeled_asset(
+                        format!("{}.{}", profile.name, tag),
+                        TextureAtlasLayout::from_grid(
+                            UVec2::splat(tile_size),
+                            expression.columns,
+                            expression.rows,
+                            None,
+                            None,
+                        ),
+                    )
+                });
+                expression.animation = expression
+                    .animation_path
+                    .as_ref()
+                    .map(|path| load_context.load(path.as_str()));
+            }
+        }
+    }
+
+    pub fn characters(&self) -> &[CharacterProfile] {
+        &self.characters
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CharacterProfile> {
+        self.characters.iter().find(|c| c.name == name)
+    }
+
+    /// The profile of whoever speaks a line, lines without a character name are the narrator's.
+    /// Unknown speakers (`_Ami`) sound the same as the known ones.
+    /// Names without a profile get the fallback character, or the narrator if there is none.
+    pub fn speaker(&self, character_name: Option<&str>) -> &CharacterProfile {
+        let name = character_name.unwrap_or_default().trim_start_matches('_');
+        if name.is_empty() {
+            return &self.narrator;
+        }
+        match self.get(name) {
+            Some(r) => r,
+            None => {
+                if name == "???" {
+                    error!("You should never hardcode character name: '???' in dialogues!");
+                } else {
+                    warn!("there is no character profile for '{}'", name);
+                }
+                self.fallback
+                    .as_deref()
+                    .and_then(|fallback| self.get(fallback))
+                    .unwrap_or(&self.narrator)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct CharactersLoader;
+
+impl AssetLoader for CharactersLoader {
+    type Asset = Characters;
+    type Settings = ();
+    type Error = CharacterError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut characters = Characters::from_ron(&bytes)?;
+        characters.load_dependencies(load_context);
+        Ok(characters)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["characters.ron"]
+    }
+}
+
+fn insert_characters(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    character_assets: Res<Assets<Characters>>,
+) {
+    match character_assets.get(&assets.characters) {
+        Some(characters) => commands.insert_resource(characters.clone()),
+        None => error!("characters are not loaded, everyone will sound like the narrator"),
+    }
+}
+
+fn reload_characters(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    character_assets: Res<Assets<Characters>>,
+    mut ev_asset: EventReader<AssetEvent<Characters>>,
+) {
+    for ev in ev_asset.read() {
+        if !ev.is_modified(&assets.characters) {
+            continue;
+        }
+        if let Some(characters) = character_assets.get(&assets.characters) {
+            info!("reloaded characters");
+            commands.insert_resource(characters.clone());
+        }
+    }
+}
+
+pub struct CharactersPlugin;
+
+impl Plugin for CharactersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Characters>()
+            .init_asset_loader::<CharactersLoader>()
+            .init_resource::<Characters>()
+            .add_systems(OnExit(GameState::AssetLoading), insert_characters)
+            .add_systems(
+                Update,
+                reload_characters.run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
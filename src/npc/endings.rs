@@ -0,0 +1,263 @@
+use std::{fmt, str::FromStr};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{aspect::Aspect, GameAssets, GameState};
+
+use super::narrator::{evaluate_aspect, NarratorDialogue};
+
+#[derive(Deserialize)]
+enum RawCondition {
+    Contains(Vec<String>),
+    Excludes(Vec<String>),
+    Score {
+        #[serde(default = "min_score")]
+        min: i32,
+        #[serde(default = "max_score")]
+        max: i32,
+    },
+    CombinedInOrder(Vec<String>),
+}
+
+fn min_score() -> i32 {
+    i32::MIN
+}
+
+fn max_score() -> i32 {
+    i32::MAX
+}
+
+#[derive(Deserialize)]
+struct RawRule {
+    node: String,
+    #[serde(default)]
+    when: Vec<RawCondition>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// All of these aspects are in the final sockets.
+    Contains(Vec<Aspect>),
+    /// None of these aspects are in the final sockets.
+    Excludes(Vec<Aspect>),
+    /// The summed up `evaluate_aspect` of the final sockets lies within `min..=max`.
+    Score { min: i32, max: i32 },
+    /// These aspects were combined in this order, not necessarily one right after the other.
+    CombinedInOrder(Vec<Aspect>),
+}
+
+impl Condition {
+    fn holds(&self, aspects: &[Aspect], combined_aspects: &[Aspect]) -> bool {
+        match self {
+            Condition::Contains(required) => required.iter().all(|a| aspects.contains(a)),
+            Condition::Excludes(excluded) => !excluded.iter().any(|a| aspects.contains(a)),
+            Condition::Score { min, max } => {
+                let score = aspects.iter().map(|a| evaluate_aspect(*a)).sum::<i32>();
+                (*min..=*max).contains(&score)
+            }
+            Condition::CombinedInOrder(order) => {
+                let mut combined = combined_aspects.iter();
+                order.iter().all(|a| combined.any(|c| c == a))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndingRule {
+    /// The yarn node of the narrator that plays this ending.
+    pub node: String,
+    /// The rule only applies if all of these hold.
+    pub conditions: Vec<Condition>,
+}
+
+#[derive(Debug)]
+pub enum EndingError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    UnknownAspect(String),
+    EmptyNode,
+    NoFallback,
+}
+
+impl fmt::Display for EndingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndingError::Io(err) => write!(f, "could not read ending rules, {}", err),
+            EndingError::Ron(err) => write!(f, "could not parse ending rules, {}", err),
+            EndingError::UnknownAspect(name) => {
+                write!(f, "unknown aspect in ending rules, '{}'", name)
+            }
+            EndingError::EmptyNode => write!(f, "ending rule without a node"),
+            EndingError::NoFallback => write!(
+                f,
+                "the last ending rule must not have any conditions, so that there is always an ending"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EndingError {}
+
+impl From<std::io::Error> for EndingError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for EndingError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Ron(err)
+    }
+}
+
+/// The ordered ending rules, the first one whose conditions hold decides the ending.
+/// Same as the aspect recipes, this is both the asset and the resource the narrator reads from.
+#[derive(Asset, Resource, TypePath, Clone, Debug)]
+pub struct EndingRules {
+    rules: Vec<EndingRule>,
+}
+
+impl Default for EndingRules {
+    fn default() -> Self {
+        Self {
+            rules: vec![EndingRule {
+                node: NarratorDialogue::GOOD_ENDING.to_string(),
+                conditions: Vec::new(),
+            }],
+        }
+    }
+}
+
+impl EndingRules {
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, EndingError> {
+        let raw_rules: Vec<RawRule> = ron::de::from_bytes(bytes)?;
+        let mut rules = Vec::new();
+        for raw in raw_rules {
+            let mut conditions = Vec::new();
+            for condition in raw.when {
+                conditions.push(match condition {
+                    RawCondition::Contains(names) => Condition::Contains(parse_aspects(&names)?),
+                    RawCondition::Excludes(names) => Condition::Excludes(parse_aspects(&names)?),
+                    RawCondition::Score { min, max } => Condition::Score { min, max },
+                    RawCondition::CombinedInOrder(names) => {
+                        Condition::CombinedInOrder(parse_aspects(&names)?)
+                    }
+                });
+            }
+            rules.push(EndingRule {
+                node: raw.node,
+                conditions,
+            });
+        }
+        Self::new(rules)
+    }
+
+    pub fn new(rules: Vec<EndingRule>) -> Result<Self, EndingError> {
+        if rules.iter().any(|rule| rule.node.is_empty()) {
+            return Err(EndingError::EmptyNode);
+        }
+        match rules.last() {
+            Some(rule) if rule.conditions.is_empty() => Ok(Self { rules }),
+            _ => Err(EndingError::NoFallback),
+        }
+    }
+
+    pub fn rules(&self) -> &[EndingRule] {
+        &self.rules
+    }
+
+    /// The ending for the aspects in the final sockets and all combinations made during the run.
+    pub fn determine(&self, aspects: &[Aspect], combined_aspects: &[Aspect]) -> NarratorDialogue {
+        match self.rules.iter().find(|rule| {
+            rule.conditions
+                .iter()
+                .all(|c| c.holds(aspects, combined_aspects))
+        }) {
+            Some(rule) => NarratorDialogue::new(&rule.node),
+            // Can't happen, the last rule is validated to always match.
+            None => NarratorDialogue::new(NarratorDialogue::GOOD_ENDING),
+        }
+    }
+}
+
+fn parse_aspects(names: &[String]) -> Result<Vec<Aspect>, EndingError> {
+    names
+        .iter()
+        .map(|name| match Aspect::from_str(name) {
+            Ok(Aspect::NotImplemented) | Err(_) => Err(EndingError::UnknownAspect(name.clone())),
+            Ok(aspect) => Ok(aspect),
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct EndingRulesLoader;
+
+impl AssetLoader for EndingRulesLoader {
+    type Asset = EndingRules;
+    type Settings = ();
+    type Error = EndingError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        EndingRules::from_ron(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["endings.ron"]
+    }
+}
+
+fn insert_ending_rules(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    ending_assets: Res<Assets<EndingRules>>,
+) {
+    match ending_assets.get(&assets.ending_rules) {
+        Some(rules) => commands.insert_resource(rules.clone()),
+        None => error!("ending rules are not loaded, there will only be the good ending"),
+    }
+}
+
+fn reload_ending_rules(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    ending_assets: Res<Assets<EndingRules>>,
+    mut ev_asset: EventReader<AssetEvent<EndingRules>>,
+) {
+    for ev in ev_asset.read() {
+        if !ev.is_modified(&assets.ending_rules) {
+            continue;
+        }
+        if let Some(rules) = ending_assets.get(&assets.ending_rules) {
+            info!("reloaded ending rules");
+            commands.insert_resource(rules.clone());
+        }
+    }
+}
+
+pub struct EndingRulesPlugin;
+
+impl Plugin for EndingRulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<EndingRules>()
+            .init_asset_loader::<EndingRulesLoader>()
+            .init_resource::<EndingRules>()
+            .add_systems(OnExit(GameState::AssetLoading), insert_ending_rules)
+            .add_systems(
+                Update,
+                reload_ending_rules.run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
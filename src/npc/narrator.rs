@@ -1,28 +1,48 @@
 use bevy_yarnspinner::events::DialogueCompleteEvent;
-use strum_macros::{Display, EnumIter, EnumString};
 
 use bevy::prelude::*;
 
 use crate::{
-    aspect::{Aspect, Socket},
+    aspect::{Aspect, Combiner, Socket, SocketMemory},
+    run::reset_resource,
+    world::CurrentLevel,
     GameState,
 };
 
+use super::endings::EndingRules;
+
 const START_DELAY: f32 = 1.0;
 const ENDING_DELAY: f32 = 2.0;
-const GOOD_ENDING_THRESHOLD: i32 = 7;
-
-#[derive(Reflect, Clone, PartialEq, EnumString, Display, Debug, Copy, EnumIter)]
-pub enum NarratorDialogue {
-    Intro,
-    GoodEnding,
-    BadEndingTooPositive,
-    BadEndingTooNegative,
+
+/// The yarn node the narrator plays, either the intro or one of the endings in the ending rules.
+#[derive(Reflect, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NarratorDialogue(String);
+
+impl NarratorDialogue {
+    pub const INTRO: &'static str = "Intro";
+    /// Used when the ending rules couldn't be loaded.
+    pub const GOOD_ENDING: &'static str = "GoodEnding";
+
+    pub fn new(node: &str) -> Self {
+        Self(node.to_string())
+    }
+
+    pub fn intro() -> Self {
+        Self::new(Self::INTRO)
+    }
+
+    pub fn node(&self) -> &str {
+        &self.0
+    }
 }
 
-#[derive(Event)]
+#[derive(Event, Clone)]
 pub struct TriggeredNarratorDialogue(pub NarratorDialogue);
 
+/// Seconds since the current narrator state was entered.
+#[derive(Resource, Default)]
+struct NarratorElapsed(f32);
+
 pub fn evaluate_aspect(aspect: Aspect) -> i32 {
     match aspect {
         Aspect::NotImplemented => 0,
@@ -43,33 +63,18 @@ pub fn evaluate_aspect(aspect: Aspect) -> i32 {
     }
 }
 
-fn determine_ending(q_sockets: &Query<&Socket>) -> NarratorDialogue {
-    let mut sum = 0;
-    for socket in q_sockets {
-        sum += evaluate_aspect(socket.aspect);
-    }
-
-    if sum > GOOD_ENDING_THRESHOLD {
-        NarratorDialogue::BadEndingTooPositive
-    } else if sum < -GOOD_ENDING_THRESHOLD {
-        NarratorDialogue::BadEndingTooNegative
-    } else {
-        NarratorDialogue::GoodEnding
-    }
-}
-
 fn trigger_intro_dialogue(
     time: Res<Time>,
     mut ev_triggered_narrator_dialogue: EventWriter<TriggeredNarratorDialogue>,
-    mut elapsed: Local<f32>,
+    mut elapsed: ResMut<NarratorElapsed>,
 ) {
-    if *elapsed > START_DELAY {
+    if elapsed.0 > START_DELAY {
         return;
     }
-    *elapsed += time.delta_seconds();
+    elapsed.0 += time.delta_seconds();
 
-    if *elapsed > START_DELAY {
-        ev_triggered_narrator_dialogue.send(TriggeredNarratorDialogue(NarratorDialogue::Intro));
+    if elapsed.0 > START_DELAY {
+        ev_triggered_narrator_dialogue.send(TriggeredNarratorDialogue(NarratorDialogue::intro()));
     }
 }
 
@@ -79,18 +84,26 @@ fn transition_to_gaming_state(mut next_state: ResMut<NextState<GameState>>) {
 
 fn trigger_ending_dialogue(
     time: Res<Time>,
-    q_sockets: Query<&Socket>,
+    ending_rules: Res<EndingRules>,
+    combiner: Res<Combiner>,
+    current_level: Res<CurrentLevel>,
+    socket_memory: Res<SocketMemory>,
+    q_sockets: Query<(&Transform, &Socket)>,
     mut ev_triggered_narrator_dialogue: EventWriter<TriggeredNarratorDialogue>,
-    mut elapsed: Local<f32>,
+    mut elapsed: ResMut<NarratorElapsed>,
 ) {
-    if *elapsed > ENDING_DELAY {
+    if elapsed.0 > ENDING_DELAY {
         return;
     }
-    *elapsed += time.delta_seconds();
+    elapsed.0 += time.delta_seconds();
 
-    if *elapsed > ENDING_DELAY {
-        ev_triggered_narrator_dialogue
-            .send(TriggeredNarratorDialogue(determine_ending(&q_sockets)));
+    if elapsed.0 > ENDING_DELAY {
+        let aspects = socket_memory
+            .merged(&current_level.0, q_sockets.iter())
+            .into_values()
+            .collect::<Vec<Aspect>>();
+        let ending = ending_rules.determine(&aspects, &combiner.combined_aspects);
+        ev_triggered_narrator_dialogue.send(TriggeredNarratorDialogue(ending));
     }
 }
 
@@ -99,6 +112,12 @@ pub struct NarratorPlugin;
 impl Plugin for NarratorPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<TriggeredNarratorDialogue>()
+            .init_resource::<NarratorElapsed>()
+            .add_systems(OnEnter(GameState::Intro), reset_resource::<NarratorElapsed>)
+            .add_systems(
+                OnEnter(GameState::Ending),
+                reset_resource::<NarratorElapsed>,
+            )
             .add_systems(
                 Update,
                 (
@@ -0,0 +1,249 @@
+use std::{fs, path::Path};
+
+use crate::{
+    aspect::Aspect,
+    harness::{shipped_characters, shipped_rules},
+};
+
+use super::characters::{CharacterError, Characters};
+use super::endings::{EndingError, EndingRules};
+
+const PATH_TO_ASSETS: &str = "assets";
+
+#[test]
+fn validate_shipped_ending_rules() {
+    let rules = shipped_rules();
+    assert!(
+        rules.rules().len() > 3,
+        "There should be more than the three original endings"
+    );
+}
+
+#[test]
+fn validate_score_endings() {
+    let rules = shipped_rules();
+    assert_eq!(
+        rules
+            .determine(&[Aspect::Forgiveness, Aspect::Elation], &[])
+            .node(),
+        "BadEndingTooPositive"
+    );
+    assert_eq!(
+        rules
+            .determine(&[Aspect::Vengefulness, Aspect::Envy], &[])
+            .node(),
+        "BadEndingTooNegative"
+    );
+    assert_eq!(
+        rules
+            .determine(&[Aspect::Joy, Aspect::Anger, Aspect::Sadness], &[])
+            .node(),
+        "GoodEnding"
+    );
+}
+
+#[test]
+fn validate_rules_apply_in_order() {
+    let rules = EndingRules::from_ron(
+        br#"[
+            (node: "Both", when: [Contains(["Joy", "Anger"])]),
+            (node: "NoFear", when: [Excludes(["Fear"]), Score(max: 0)]),
+            (node: "Ordered", when: [CombinedInOrder(["Hatred", "Pride"])]),
+            (node: "Fallback"),
+        ]"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        rules.determine(&[Aspect::Joy, Aspect::Anger], &[]).node(),
+        "Both"
+    );
+    assert_eq!(rules.determine(&[Aspect::Anger], &[]).node(), "NoFear");
+    assert_eq!(
+        rules.determine(&[Aspect::Anger, Aspect::Fear], &[]).node(),
+        "Fallback"
+    );
+    assert_eq!(
+        rules
+            .determine(
+                &[Aspect::Fear],
+                &[Aspect::Hatred, Aspect::Nostalgia, Aspect::Pride]
+            )
+            .node(),
+        "Ordered"
+    );
+    assert_eq!(
+        rules
+            .determine(&[Aspect::Fear], &[Aspect::Pride, Aspect::Hatred])
+            .node(),
+        "Fallback"
+    );
+}
+
+#[test]
+fn reject_invalid_ending_rules() {
+    let result =
+        EndingRules::from_ron(br#"[(node: "A", when: [Contains(["Boredom"])]), (node: "B")]"#);
+    assert!(matches!(result, Err(EndingError::UnknownAspect(name)) if name == "Boredom"));
+
+    let result = EndingRules::from_ron(br#"[(node: "A", when: [Score(min: 3)])]"#);
+    assert!(matches!(result, Err(EndingError::NoFallback)));
+
+    let result = EndingRules::from_ron(br#"[]"#);
+    assert!(matches!(result, Err(EndingError::NoFallback)));
+
+    let result = EndingRules::from_ron(br#"[(node: "")]"#);
+    assert!(matches!(result, Err(EndingError::EmptyNode)));
+}
+
+#[test]
+fn validate_shipped_characters() {
+    let characters = shipped_characters();
+
+    assert!(characters.get("Ami").is_some());
+    assert!(characters.get("Ima").is_some());
+    for profile in characters.characters() {
+        for path in [&profile.portrait_path, &profile.blip_path]
+            .into_iter()
+            .flatten()
+        {
+            assert!(
+                Path::new(PATH_TO_ASSETS).join(path).is_file(),
+                "The asset '{}' of '{}' doesn't exist",
+                path,
+                profile.name
+            );
+        }
+    }
+}
+
+#[test]
+fn validate_expression_assets() {
+    let characters = shipped_characters();
+
+    for profile in characters.characters() {
+        for (tag, expression) in &profile.expressions {
+            assert!(
+                Path::new(PATH_TO_ASSETS)
+                    .join(&expression.image_path)
+                    .is_file(),
+                "The image '{}' of '{}#{}' doesn't exist",
+                expression.image_path,
+                profile.name,
+                tag
+            );
+
+            let animation_path = match &expression.animation_path {
+                Some(r) => r,
+                None => continue,
+            };
+            let (path, clip) = animation_path
+                .split_once('#')
+                .unwrap_or_else(|| panic!("'{animation_path}' doesn't name a trickfilm clip"));
+            let contents = fs::read_to_string(Path::new(PATH_TO_ASSETS).join(path))
+                .unwrap_or_else(|_| panic!("The animation '{path}' doesn't exist"));
+            assert!(
+                contents.contains(&format!("\"{clip}\":")),
+                "The clip '{clip}' of '{}#{tag}' is not in '{path}'",
+                profile.name
+            );
+        }
+    }
+}
+
+#[test]
+fn validate_expression_picks_first_tag() {
+    let characters = Characters::from_ron(
+        br#"(
+            narrator: (typing_speed: 20.0),
+            characters: [(name: "Ami", typing_speed: 18.0, expressions: {
+                "smile": (image: "ami_smile.png"),
+                "sad": (image: "ami_sad.png"),
+            })],
+        )"#,
+    )
+    .unwrap();
+    let ami = characters.get("Ami").unwrap();
+    let image = |tags: &[&str]| {
+        let tags = tags
+            .iter()
+            .map(|tag| tag.to_string())
+            .collect::<Vec<String>>();
+        ami.expression(&tags)
+            .map(|expression| expression.image_path.clone())
+    };
+
+    assert_eq!(image(&["sad", "smile"]).as_deref(), Some("ami_sad.png"));
+    assert_eq!(image(&["bored", "smile"]).as_deref(), Some("ami_smile.png"));
+    // Unknown tags and lines without tags show the neutral portrait.
+    assert_eq!(image(&["bored"]), None);
+    assert_eq!(image(&[]), None);
+}
+
+#[test]
+fn validate_character_speakers() {
+    let characters = Characters::from_ron(
+        br#"(
+            narrator: (typing_speed: 20.0),
+            characters: [(name: "Ami", display_name: "Amiko", typing_speed: 18.0, punctuation_pauses: {"?": 2.0})],
+        )"#,
+    )
+    .unwrap();
+
+    let ami = characters.speaker(Some("Ami"));
+    assert_eq!(ami.display_name, "Amiko");
+    assert_eq!(ami.punctuation_pause_multiplier("?"), 2.0);
+    assert_eq!(ami.punctuation_pause_multiplier("."), 1.0);
+    // Unknown speakers sound like the known ones, only their name is hidden.
+    assert_eq!(characters.speaker(Some("_Ami")).name, "Ami");
+    assert_eq!(characters.speaker(None).typing_speed, 20.0);
+}
+
+#[test]
+fn validate_unknown_speaker_fallback() {
+    // Same as before there were profiles, unknown names get Ima's voice and a portrait.
+    let characters = shipped_characters();
+    let nobody = characters.speaker(Some("Nobody"));
+    assert_eq!(nobody.name, "Ima");
+    assert!(nobody.blip_path.is_some());
+    assert!(nobody.portrait_path.is_some());
+
+    let characters = Characters::from_ron(
+        br#"(narrator: (typing_speed: 20.0), characters: [(name: "Ami", typing_speed: 18.0)])"#,
+    )
+    .unwrap();
+    assert_eq!(characters.speaker(Some("Nobody")).typing_speed, 20.0);
+}
+
+#[test]
+fn reject_invalid_characters() {
+    let result = Characters::from_ron(
+        br#"(narrator: (typing_speed: 20.0), characters: [(name: "Ami", typing_speed: 18.0), (name: "Ami", typing_speed: 20.0)])"#,
+    );
+    assert!(matches!(result, Err(CharacterError::DuplicateName(name)) if name == "Ami"));
+
+    let result = Characters::from_ron(
+        br#"(narrator: (typing_speed: 20.0), characters: [(name: "_Ami", typing_speed: 18.0)])"#,
+    );
+    assert!(matches!(result, Err(CharacterError::ReservedName(_))));
+
+    let result = Characters::from_ron(
+        br#"(narrator: (typing_speed: 20.0), characters: [(name: "", typing_speed: 18.0)])"#,
+    );
+    assert!(matches!(result, Err(CharacterError::EmptyName)));
+
+    let result = Characters::from_ron(br#"(narrator: (typing_speed: 0.0), characters: [])"#);
+    assert!(matches!(result, Err(CharacterError::InvalidSpeed(_))));
+
+    let result = Characters::from_ron(
+        br#"(narrator: (typing_speed: 20.0), characters: [(name: "Ami", typing_speed: 18.0, expressions: {"sad": (image: "ami_sad.png", animation: "ami.trickfilm#sad")})])"#,
+    );
+    assert!(
+        matches!(result, Err(CharacterError::AnimationWithoutTiles(expression)) if expression == "Ami#sad")
+    );
+
+    let result = Characters::from_ron(
+        br#"(narrator: (typing_speed: 20.0), fallback: "Ima", characters: [(name: "Ami", typing_speed: 18.0)])"#,
+    );
+    assert!(matches!(result, Err(CharacterError::UnknownFallback(name)) if name == "Ima"));
+}
@@ -5,6 +5,7 @@ use bevy_yarnspinner::events::DialogueCompleteEvent;
 use crate::{
     aspect::CombinedAspect,
     audio::PlaySound,
+    run::RunScoped,
     world::{camera::YSort, PlayerWentToBed, TriggerFirstImaDialogue},
     GameAssets, GameState,
 };
@@ -14,6 +15,13 @@ use super::Player;
 #[derive(Component)]
 struct Ima;
 
+/// Spawn Ima next to the player, e.g. from the `spawn_ima` yarn command.
+#[derive(Event)]
+pub struct SpawnIma;
+
+#[derive(Event)]
+pub struct DespawnIma;
+
 const OFFSET: Vec3 = Vec3::new(64.0, 0.0, 0.0);
 
 fn spawn_ima(
@@ -48,6 +56,7 @@ fn spawn_ima(
         .repeat();
 
     commands.spawn((
+        RunScoped,
         Ima,
         YSort(0.0),
         animator,
@@ -77,15 +86,22 @@ pub struct ImaPlugin;
 
 impl Plugin for ImaPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                spawn_ima.run_if(on_event::<CombinedAspect>().or_else(
-                    on_event::<TriggerFirstImaDialogue>().or_else(on_event::<PlayerWentToBed>()),
-                )),
-                despawn_ima.run_if(on_event::<DialogueCompleteEvent>()),
-            )
-                .run_if(in_state(GameState::Gaming)),
-        );
+        app.add_event::<SpawnIma>()
+            .add_event::<DespawnIma>()
+            .add_systems(
+                Update,
+                (
+                    spawn_ima.run_if(
+                        on_event::<CombinedAspect>()
+                            .or_else(on_event::<TriggerFirstImaDialogue>())
+                            .or_else(on_event::<PlayerWentToBed>())
+                            .or_else(on_event::<SpawnIma>()),
+                    ),
+                    despawn_ima.run_if(
+                        on_event::<DialogueCompleteEvent>().or_else(on_event::<DespawnIma>()),
+                    ),
+                )
+                    .run_if(in_state(GameState::Gaming)),
+            );
     }
 }
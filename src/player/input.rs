@@ -3,13 +3,25 @@ use bevy::input::InputSystem;
 use bevy::prelude::*;
 use bevy::window::{PrimaryWindow, Window};
 
+use serde::{Deserialize, Serialize};
+
+use crate::ui::{dialogue_log_closed, keybindings_menu_closed, settings_menu_closed};
 use crate::world::camera::MainCamera;
-use crate::GameState;
+use crate::{GameState, PauseState};
+
+use super::input_map::{InputAction, InputDevice, InputDevices, InputMap};
+
+// How far the stick has to be tilted to move through dialogue options.
+const STICK_DIRECTION_THRESHOLD: f32 = 0.5;
 
 #[derive(Resource, Default)]
 pub struct MouseWorldCoords(pub Vec2);
 
-#[derive(Resource, Default)]
+/// The systems that fill in `PlayerInput` from the devices.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerInputSystem;
+
+#[derive(Resource, Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct PlayerInput {
     pub move_direction: Vec2,
     pub scroll: f32,
@@ -17,11 +29,15 @@ pub struct PlayerInput {
     pub escape: bool,
 
     pub select_socket: bool,
+    pub undo_combination: bool,
 
     pub start_dialogue: bool,
     pub dialogue_confirm: bool,
     pub dialogue_continue: bool,
     pub dialogue_direction: i8,
+    /// Older recordings don't have this yet.
+    #[serde(default)]
+    pub open_dialogue_log: bool,
 
     pub toggle_fullscreen: bool,
     pub toggle_debug: bool,
@@ -79,12 +95,16 @@ fn fetch_scroll_events(
     }
 }
 
-fn input_scroll(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
+fn input_scroll(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut player_input: ResMut<PlayerInput>,
+) {
     let mut zoom = 0.0;
-    if keys.just_pressed(KeyCode::Backspace) {
+    if input_map.just_pressed(InputAction::ZoomIn, &devices) {
         zoom -= 1.0;
     }
-    if keys.just_pressed(KeyCode::Minus) {
+    if input_map.just_pressed(InputAction::ZoomOut, &devices) {
         zoom += 1.0;
     }
 
@@ -93,71 +113,140 @@ fn input_scroll(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<Player
     }
 }
 
-fn player_movement(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
+fn player_movement(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut player_input: ResMut<PlayerInput>,
+) {
     let mut direction = Vec2::default();
 
-    if keys.pressed(KeyCode::KeyJ) || keys.pressed(KeyCode::KeyS) {
+    if input_map.pressed(InputAction::MoveDown, &devices) {
         direction += Vec2::new(0.0, -1.0);
     }
-    if keys.pressed(KeyCode::KeyK) || keys.pressed(KeyCode::KeyW) {
+    if input_map.pressed(InputAction::MoveUp, &devices) {
         direction += Vec2::new(0.0, 1.0);
     }
-    if keys.pressed(KeyCode::KeyF) || keys.pressed(KeyCode::KeyD) {
+    if input_map.pressed(InputAction::MoveRight, &devices) {
         direction += Vec2::new(1.0, 0.0);
     }
-    if keys.pressed(KeyCode::KeyA) {
+    if input_map.pressed(InputAction::MoveLeft, &devices) {
         direction += Vec2::new(-1.0, 0.0);
     }
+    direction += devices.left_stick();
 
     player_input.move_direction = direction.normalize_or_zero();
 }
 
-fn input_running(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
-    player_input.running = keys.pressed(KeyCode::ShiftLeft);
+fn input_running(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    player_input.running = input_map.pressed(InputAction::Run, &devices);
+}
+
+fn input_escape(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    player_input.escape = input_map.just_pressed(InputAction::Escape, &devices);
 }
 
-fn input_escape(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
-    player_input.escape = keys.just_pressed(KeyCode::Escape);
+fn select_socket(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    player_input.select_socket = input_map.just_pressed(InputAction::Select, &devices);
 }
 
-fn select_socket(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
-    player_input.select_socket = keys.just_pressed(KeyCode::KeyE);
+fn undo_combination(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    player_input.undo_combination = input_map.just_pressed(InputAction::Undo, &devices);
 }
 
 fn input_dialogue(
-    keys: Res<ButtonInput<KeyCode>>,
-    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    input_map: Res<InputMap>,
+    devices: InputDevices,
     mut player_input: ResMut<PlayerInput>,
+    mut stick_direction: Local<i8>,
 ) {
     let mut direction = 0;
 
-    if keys.just_pressed(KeyCode::KeyJ)
-        || keys.just_pressed(KeyCode::KeyS)
-        || keys.just_pressed(KeyCode::ArrowDown)
-    {
+    if input_map.just_pressed(InputAction::DialogueDown, &devices) {
         direction -= 1;
     }
-    if keys.just_pressed(KeyCode::KeyK)
-        || keys.just_pressed(KeyCode::KeyW)
-        || keys.just_pressed(KeyCode::ArrowUp)
-    {
+    if input_map.just_pressed(InputAction::DialogueUp, &devices) {
         direction += 1;
     }
-    player_input.dialogue_direction = direction;
 
-    player_input.start_dialogue = keys.just_pressed(KeyCode::KeyE);
-    player_input.dialogue_confirm = keys.just_pressed(KeyCode::Enter);
+    // Only count the stick once when it gets tilted, not every frame it's held.
+    let stick = devices.left_stick();
+    let new_stick_direction = if stick.y.abs() > STICK_DIRECTION_THRESHOLD {
+        stick.y.signum() as i8
+    } else {
+        0
+    };
+    if new_stick_direction != *stick_direction {
+        direction += new_stick_direction;
+        *stick_direction = new_stick_direction;
+    }
+    player_input.dialogue_direction = direction;
 
+    player_input.start_dialogue = input_map.just_pressed(InputAction::Select, &devices);
+    player_input.dialogue_confirm = input_map.just_pressed(InputAction::DialogueConfirm, &devices);
     player_input.dialogue_continue =
-        keys.just_pressed(KeyCode::Space) || mouse_buttons.just_pressed(MouseButton::Left);
+        input_map.just_pressed(InputAction::DialogueContinue, &devices);
+    player_input.open_dialogue_log = input_map.just_pressed(InputAction::DialogueLog, &devices);
+}
+
+fn toggle_fullscreen(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    player_input.toggle_fullscreen =
+        input_map.just_pressed(InputAction::ToggleFullscreen, &devices);
+}
+
+fn toggle_debug(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    player_input.toggle_debug = input_map.just_pressed(InputAction::ToggleDebug, &devices);
 }
 
-fn toggle_fullscreen(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
-    player_input.toggle_fullscreen = keys.just_pressed(KeyCode::KeyB);
+fn track_last_input_device(devices: InputDevices, mut input_device: ResMut<InputDevice>) {
+    if devices.any_gamepad_just_pressed() || devices.left_stick() != Vec2::ZERO {
+        input_device.set_if_neq(InputDevice::Gamepad);
+    } else if devices.any_keyboard_just_pressed() {
+        input_device.set_if_neq(InputDevice::Keyboard);
+    }
 }
 
-fn toggle_debug(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
-    player_input.toggle_debug = keys.just_pressed(KeyCode::F3);
+fn handle_gamepad_connections(
+    gamepads: Res<Gamepads>,
+    mut input_device: ResMut<InputDevice>,
+    mut ev_gamepad_connection: EventReader<GamepadConnectionEvent>,
+) {
+    for ev in ev_gamepad_connection.read() {
+        match &ev.connection {
+            GamepadConnection::Connected(info) => {
+                info!("gamepad {} connected, {}", ev.gamepad.id, info.name)
+            }
+            GamepadConnection::Disconnected => {
+                info!("gamepad {} disconnected", ev.gamepad.id);
+                if gamepads.iter().next().is_none() {
+                    input_device.set_if_neq(InputDevice::Keyboard);
+                }
+            }
+        }
+    }
 }
 
 pub struct InputPlugin;
@@ -174,13 +263,30 @@ impl Plugin for InputPlugin {
                 input_running,
                 input_escape,
                 select_socket,
+                undo_combination,
                 input_dialogue,
                 toggle_fullscreen,
                 toggle_debug,
             )
                 .run_if(not(in_state(GameState::AssetLoading)))
+                .run_if(keybindings_menu_closed)
+                .run_if(settings_menu_closed)
+                .run_if(dialogue_log_closed)
+                .run_if(not(in_state(PauseState::Paused)))
+                .in_set(PlayerInputSystem)
+                .after(InputSystem),
+        )
+        .add_systems(
+            PreUpdate,
+            (
+                handle_gamepad_connections.run_if(on_event::<GamepadConnectionEvent>()),
+                track_last_input_device,
+            )
+                .chain()
                 .after(InputSystem),
         )
+        .insert_resource(InputMap::load())
+        .init_resource::<InputDevice>()
         .init_resource::<PlayerInput>()
         .init_resource::<MouseWorldCoords>()
         .add_systems(PreUpdate, reset_player_input.before(InputSystem));
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+use crate::utils::storage;
+
+const CONFIG_FILE: &str = "input.ron";
+/// Stick deflections below this are ignored, the rest gets rescaled to `0..1`.
+const STICK_DEAD_ZONE: f32 = 0.25;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, EnumIter, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Run,
+    Select,
+    Undo,
+    DialogueUp,
+    DialogueDown,
+    DialogueConfirm,
+    DialogueContinue,
+    DialogueLog,
+    Escape,
+    ToggleFullscreen,
+    ToggleDebug,
+    ZoomIn,
+    ZoomOut,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+/// The device the player used last, hints show the glyphs of this device.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+/// All the devices that bindings can be read from.
+#[derive(SystemParam)]
+pub struct InputDevices<'w> {
+    pub keys: Res<'w, ButtonInput<KeyCode>>,
+    pub mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    pub gamepads: Res<'w, Gamepads>,
+    pub gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    pub gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl InputDevices<'_> {
+    fn gamepad_button_pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.gamepads.iter().any(|gamepad| {
+            self.gamepad_buttons
+                .pressed(GamepadButton::new(gamepad, button_type))
+        })
+    }
+
+    fn gamepad_button_just_pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.gamepads.iter().any(|gamepad| {
+            self.gamepad_buttons
+                .just_pressed(GamepadButton::new(gamepad, button_type))
+        })
+    }
+
+    /// Left stick of the first gamepad that is outside of the dead zone.
+    pub fn left_stick(&self) -> Vec2 {
+        for gamepad in self.gamepads.iter() {
+            let x = self
+                .gamepad_axes
+                .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or(0.0);
+            let y = self
+                .gamepad_axes
+                .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or(0.0);
+
+            let stick = Vec2::new(x, y);
+            let length = stick.length();
+            if length > STICK_DEAD_ZONE {
+                let scaled = ((length - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE)).min(1.0);
+                return stick / length * scaled;
+            }
+        }
+        Vec2::ZERO
+    }
+
+    pub fn any_keyboard_just_pressed(&self) -> bool {
+        self.keys.get_just_pressed().next().is_some()
+            || self.mouse_buttons.get_just_pressed().next().is_some()
+    }
+
+    pub fn any_gamepad_just_pressed(&self) -> bool {
+        self.gamepad_buttons.get_just_pressed().next().is_some()
+    }
+}
+
+impl Binding {
+    /// Short, human readable name, used in the UI.
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{:?}", key);
+                let name = name
+                    .trim_start_matches("Key")
+                    .trim_start_matches("Digit")
+                    .trim_start_matches("Arrow");
+                name.to_string()
+            }
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+            Binding::Gamepad(button) => match button {
+                GamepadButtonType::South => "A".to_string(),
+                GamepadButtonType::East => "B".to_string(),
+                GamepadButtonType::West => "X".to_string(),
+                GamepadButtonType::North => "Y".to_string(),
+                GamepadButtonType::LeftTrigger => "LB".to_string(),
+                GamepadButtonType::RightTrigger => "RB".to_string(),
+                GamepadButtonType::LeftTrigger2 => "LT".to_string(),
+                GamepadButtonType::RightTrigger2 => "RT".to_string(),
+                GamepadButtonType::LeftThumb => "LS".to_string(),
+                GamepadButtonType::RightThumb => "RS".to_string(),
+                GamepadButtonType::Select => "Back".to_string(),
+                GamepadButtonType::Start => "Start".to_string(),
+                GamepadButtonType::DPadUp => "Up".to_string(),
+                GamepadButtonType::DPadDown => "Down".to_string(),
+                GamepadButtonType::DPadLeft => "Left".to_string(),
+                GamepadButtonType::DPadRight => "Right".to_string(),
+                _ => format!("{:?}", button),
+            },
+        }
+    }
+
+    fn device(&self) -> InputDevice {
+        match self {
+            Binding::Key(_) | Binding::Mouse(_) => InputDevice::Keyboard,
+            Binding::Gamepad(_) => InputDevice::Gamepad,
+        }
+    }
+}
+
+fn default_bindings(action: InputAction) -> Vec<Binding> {
+    use Binding::*;
+
+    match action {
+        InputAction::MoveUp => vec![
+            Key(KeyCode::KeyW),
+            Key(KeyCode::KeyK),
+            Gamepad(GamepadButtonType::DPadUp),
+        ],
+        InputAction::MoveDown => vec![
+            Key(KeyCode::KeyS),
+            Key(KeyCode::KeyJ),
+            Gamepad(GamepadButtonType::DPadDown),
+        ],
+        InputAction::MoveLeft => vec![Key(KeyCode::KeyA), Gamepad(GamepadButtonType::DPadLeft)],
+        InputAction::MoveRight => vec![
+            Key(KeyCode::KeyD),
+            Key(KeyCode::KeyF),
+            Gamepad(GamepadButtonType::DPadRight),
+        ],
+        InputAction::Run => vec![
+            Key(KeyCode::ShiftLeft),
+            Gamepad(GamepadButtonType::RightTrigger2),
+            Gamepad(GamepadButtonType::RightTrigger),
+        ],
+        InputAction::Select => vec![Key(KeyCode::KeyE), Gamepad(GamepadButtonType::West)],
+        InputAction::Undo => vec![Key(KeyCode::KeyZ), Gamepad(GamepadButtonType::North)],
+        InputAction::DialogueUp => vec![
+            Key(KeyCode::KeyW),
+            Key(KeyCode::KeyK),
+            Key(KeyCode::ArrowUp),
+            Gamepad(GamepadButtonType::DPadUp),
+        ],
+        InputAction::DialogueDown => vec![
+            Key(KeyCode::KeyS),
+            Key(KeyCode::KeyJ),
+            Key(KeyCode::ArrowDown),
+            Gamepad(GamepadButtonType::DPadDown),
+        ],
+        InputAction::DialogueConfirm => {
+            vec![Key(KeyCode::Enter), Gamepad(GamepadButtonType::South)]
+        }
+        InputAction::DialogueContinue => vec![
+            Key(KeyCode::Space),
+            Mouse(MouseButton::Left),
+            Gamepad(GamepadButtonType::South),
+        ],
+        InputAction::DialogueLog => vec![Key(KeyCode::KeyL), Gamepad(GamepadButtonType::Select)],
+        InputAction::Escape => vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::Start)],
+        InputAction::ToggleFullscreen => vec![Key(KeyCode::KeyB)],
+        InputAction::ToggleDebug => vec![Key(KeyCode::F3)],
+        InputAction::ZoomIn => vec![Key(KeyCode::Backspace)],
+        InputAction::ZoomOut => vec![Key(KeyCode::Minus)],
+    }
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct InputMap {
+    bindings: HashMap<InputAction, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            bindings: InputAction::iter()
+                .map(|action| (action, default_bindings(action)))
+                .collect(),
+        }
+    }
+}
+
+impl InputMap {
+    pub fn bindings(&self, action: InputAction) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], |b| b.as_slice())
+    }
+
+    /// Replace the bindings of `action` that belong to the same device as `binding`.
+    /// The new binding becomes the primary one of that device.
+    pub fn rebind(&mut self, action: InputAction, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|b| b.device() != binding.device());
+        bindings.insert(0, binding);
+    }
+
+    /// The first keyboard binding of the action, this is the one we show in hints.
+    pub fn primary_key(&self, action: InputAction) -> Option<KeyCode> {
+        self.bindings(action).iter().find_map(|b| match b {
+            Binding::Key(key) => Some(*key),
+            _ => None,
+        })
+    }
+
+    /// The first gamepad binding of the action, this is the one we show in hints.
+    pub fn primary_gamepad_button(&self, action: InputAction) -> Option<GamepadButtonType> {
+        self.bindings(action).iter().find_map(|b| match b {
+            Binding::Gamepad(button) => Some(*button),
+            _ => None,
+        })
+    }
+
+    /// Label of the primary binding on the given device, empty if the action isn't bound there.
+    pub fn hint(&self, action: InputAction, device: InputDevice) -> String {
+        let binding = match device {
+            InputDevice::Gamepad => self.primary_gamepad_button(action).map(Binding::Gamepad),
+            InputDevice::Keyboard => self.primary_key(action).map(Binding::Key),
+        };
+        binding.map(|b| b.label()).unwrap_or_default()
+    }
+
+    pub fn is_default_primary_key(&self, action: InputAction) -> bool {
+        let default_key = default_bindings(action).iter().find_map(|b| match b {
+            Binding::Key(key) => Some(*key),
+            _ => None,
+        });
+        self.primary_key(action) == default_key
+    }
+
+    pub fn pressed(&self, action: InputAction, devices: &InputDevices) -> bool {
+        self.bindings(action).iter().any(|b| match b {
+            Binding::Key(key) => devices.keys.pressed(*key),
+            Binding::Mouse(button) => devices.mouse_buttons.pressed(*button),
+            Binding::Gamepad(button) => devices.gamepad_button_pressed(*button),
+        })
+    }
+
+    pub fn just_pressed(&self, action: InputAction, devices: &InputDevices) -> bool {
+        self.bindings(action).iter().any(|b| match b {
+            Binding::Key(key) => devices.keys.just_pressed(*key),
+            Binding::Mouse(button) => devices.mouse_buttons.just_pressed(*button),
+            Binding::Gamepad(button) => devices.gamepad_button_just_pressed(*button),
+        })
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Load the input map from the config file.
+    /// Actions that are missing in the config fall back to their default bindings.
+    pub fn load() -> Self {
+        let mut input_map = Self::default();
+        let contents = match storage::read(CONFIG_FILE) {
+            Some(r) => r,
+            None => return input_map,
+        };
+
+        match ron::from_str::<Self>(&contents) {
+            Ok(loaded) => input_map.bindings.extend(loaded.bindings),
+            Err(err) => error!("failed to parse input config, using defaults, {}", err),
+        }
+        input_map
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to serialize input config, {}", err);
+                return;
+            }
+        };
+        if let Err(err) = storage::write(CONFIG_FILE, &contents) {
+            error!("failed to write input config, {}", err);
+        }
+    }
+}
@@ -1,10 +1,16 @@
 pub mod input;
+pub mod input_map;
+pub mod replay;
 
 mod audio;
 mod ima;
 mod movement;
 mod spawn;
 
+#[cfg(test)]
+mod test;
+
+pub use ima::{DespawnIma, SpawnIma};
 pub use spawn::PlayerSpawnPos;
 
 use bevy::prelude::*;
@@ -21,6 +27,7 @@ impl Plugin for PlayerPlugin {
             movement::PlayerMovementPlugin,
             ima::ImaPlugin,
             audio::PlayerAudioPlugin,
+            replay::ReplayPlugin,
         ));
     }
 }
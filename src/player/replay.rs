@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{utils::storage, GameRng};
+
+use super::input::{PlayerInput, PlayerInputSystem};
+
+/// Every frame advances the game by exactly this much while recording or replaying,
+/// otherwise the same input could play out differently depending on the frame rate.
+pub const REPLAY_TIMESTEP: f64 = 1.0 / 60.0;
+/// Frames between writes of the recording, so that a crash loses at most the last ten seconds.
+const FLUSH_INTERVAL: usize = 600;
+
+/// The player input of a whole session, frame by frame.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub seed: u64,
+    /// Seconds every frame advanced the game by.
+    pub timestep: f64,
+    pub frames: Vec<PlayerInput>,
+}
+
+impl InputRecording {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            timestep: REPLAY_TIMESTEP,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn from_ron(contents: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(contents)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+}
+
+/// Only the `PlayerInput` gets recorded. Menus (title screen, pause, settings, gallery and codex)
+/// read the `InputMap` and devices directly, so a replay has to be started from the same menu
+/// and the menus have to be navigated by hand.
+#[derive(Resource, Default, Debug)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    /// Record the input and write it to `file` every few seconds and when the game exits.
+    Recording {
+        file: String,
+        recording: InputRecording,
+    },
+    /// Feed the recorded input back instead of reading it from the devices.
+    Replaying {
+        recording: InputRecording,
+        frame: usize,
+    },
+}
+
+impl ReplayMode {
+    /// `--record <file>` records the session, `--replay <file>` plays one back.
+    /// The files live in the same place as the save file.
+    pub fn from_args(args: impl Iterator<Item = String>) -> Self {
+        Self::parse_args(args, storage::read)
+    }
+
+    /// Same as `from_args`, `read` returns the contents of a recording file.
+    pub(super) fn parse_args(
+        args: impl Iterator<Item = String>,
+        read: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let args = args.collect::<Vec<String>>();
+        for window in args.windows(2) {
+            match window[0].as_str() {
+                "--record" => {
+                    return ReplayMode::Recording {
+                        file: window[1].clone(),
+                        recording: InputRecording::new(thread_rng().gen()),
+                    }
+                }
+                "--replay" => return Self::load(&window[1], read),
+                _ => {}
+            }
+        }
+        ReplayMode::Off
+    }
+
+    fn load(file: &str, read: impl Fn(&str) -> Option<String>) -> Self {
+        let contents = match read(file) {
+            Some(r) => r,
+            None => {
+                error!("could not find the recording '{}', not replaying", file);
+                return ReplayMode::Off;
+            }
+        };
+        match InputRecording::from_ron(&contents) {
+            Ok(recording) => ReplayMode::replaying(recording),
+            Err(err) => {
+                error!("failed to parse the recording '{}', {}", file, err);
+                ReplayMode::Off
+            }
+        }
+    }
+
+    pub fn replaying(recording: InputRecording) -> Self {
+        ReplayMode::Replaying {
+            recording,
+            frame: 0,
+        }
+    }
+
+    /// The rng seed for this session, random unless it's set by a recording.
+    pub fn seed(&self) -> u64 {
+        match self {
+            ReplayMode::Off => thread_rng().gen(),
+            ReplayMode::Recording { recording, .. } | ReplayMode::Replaying { recording, .. } => {
+                recording.seed
+            }
+        }
+    }
+
+    /// How time advances in this mode, fixed steps whenever input gets recorded or replayed.
+    pub fn time_update_strategy(&self) -> TimeUpdateStrategy {
+        match self {
+            ReplayMode::Off => TimeUpdateStrategy::Automatic,
+            ReplayMode::Recording { recording, .. } | ReplayMode::Replaying { recording, .. } => {
+                TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(recording.timestep))
+            }
+        }
+    }
+}
+
+fn record_input(player_input: Res<PlayerInput>, mut replay_mode: ResMut<ReplayMode>) {
+    if let ReplayMode::Recording { recording, .. } = replay_mode.as_mut() {
+        recording.frames.push(player_input.clone());
+    }
+}
+
+fn replay_input(mut player_input: ResMut<PlayerInput>, mut replay_mode: ResMut<ReplayMode>) {
+    let (recording, frame) = match replay_mode.as_mut() {
+        ReplayMode::Replaying { recording, frame } => (recording, frame),
+        _ => return,
+    };
+
+    match recording.frames.get(*frame).cloned() {
+        Some(input) => {
+            *player_input = input;
+            *frame += 1;
+        }
+        None => {
+            info!("replay finished after {} frames", frame);
+            *replay_mode = ReplayMode::Off;
+        }
+    }
+}
+
+fn save_recording(replay_mode: Res<ReplayMode>) {
+    let (file, recording) = match replay_mode.as_ref() {
+        ReplayMode::Recording { file, recording } => (file, recording),
+        _ => return,
+    };
+
+    let contents = match recording.to_ron() {
+        Ok(r) => r,
+        Err(err) => {
+            error!("failed to serialize the recording, {}", err);
+            return;
+        }
+    };
+    match storage::write(file, &contents) {
+        Ok(()) => info!(
+            "wrote {} recorded frames to '{}'",
+            recording.frames.len(),
+            file
+        ),
+        Err(err) => error!("failed to write the recording, {}", err),
+    }
+}
+
+fn flush_due(replay_mode: Res<ReplayMode>) -> bool {
+    match replay_mode.as_ref() {
+        ReplayMode::Recording { recording, .. } => {
+            !recording.frames.is_empty() && recording.frames.len() % FLUSH_INTERVAL == 0
+        }
+        _ => false,
+    }
+}
+
+fn is_recording(replay_mode: Res<ReplayMode>) -> bool {
+    matches!(*replay_mode, ReplayMode::Recording { .. })
+}
+
+fn is_replaying(replay_mode: Res<ReplayMode>) -> bool {
+    matches!(*replay_mode, ReplayMode::Replaying { .. })
+}
+
+/// Applies a replay mode to the app, along with the rng seed and the time steps that go with it.
+pub fn set_replay_mode(app: &mut App, replay_mode: ReplayMode) {
+    app.insert_resource(GameRng::seeded(replay_mode.seed()))
+        .insert_resource(replay_mode.time_update_strategy())
+        .insert_resource(replay_mode);
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        // Tests set their own mode, the arguments there are meant for the test runner.
+        let replay_mode = if cfg!(test) {
+            ReplayMode::Off
+        } else {
+            ReplayMode::from_args(std::env::args().skip(1))
+        };
+        set_replay_mode(app, replay_mode);
+
+        app.add_systems(
+            PreUpdate,
+            (
+                record_input.run_if(is_recording),
+                replay_input.run_if(is_replaying),
+            )
+                .after(PlayerInputSystem),
+        )
+        .add_systems(
+            Last,
+            save_recording.run_if(on_event::<AppExit>().or_else(flush_due)),
+        );
+    }
+}
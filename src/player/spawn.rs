@@ -3,7 +3,8 @@ use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use bevy_trickfilm::prelude::*;
 
-use crate::world::camera::YSort;
+use crate::run::RunScoped;
+use crate::world::{camera::YSort, LevelEntrance};
 use crate::{GameAssets, GameState};
 
 use super::{Player, PLAYER_PIVOT};
@@ -23,18 +24,28 @@ struct PlayerSpawnPosBundle {
     player_spawn_pos: PlayerSpawnPos,
     #[grid_coords]
     grid_coords: GridCoords,
-    #[worldly]
-    worldly: Worldly,
 }
 
 fn spawn_player(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    q_player: Query<(), With<Player>>,
     q_player_spawn_pos: Query<&GridCoords, Added<PlayerSpawnPos>>,
+    q_level_entrances: Query<&GridCoords, Added<LevelEntrance>>,
 ) {
-    let grid_coords = match q_player_spawn_pos.get_single() {
-        Ok(r) => r,
-        Err(_) => return,
+    // The player carries over between levels, only the first level that spawns places them.
+    if !q_player.is_empty() {
+        return;
+    }
+    // Only the start level has a spawn position. When continuing a save in another level
+    // we start at one of its entrances and the save moves us to the right spot.
+    let grid_coords = match q_player_spawn_pos
+        .iter()
+        .next()
+        .or_else(|| q_level_entrances.iter().next())
+    {
+        Some(r) => r,
+        None => return,
     };
 
     let collider = commands
@@ -58,6 +69,7 @@ fn spawn_player(
     );
     commands
         .spawn((
+            RunScoped,
             Player {
                 can_move: true,
                 x_value_tutorial_dialogue: f32::MAX,
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use rand::Rng;
+use strum::IntoEnumIterator;
+
+use crate::GameRng;
+
+use super::{
+    input::PlayerInput,
+    input_map::{Binding, InputAction, InputMap},
+    replay::{InputRecording, ReplayMode},
+};
+
+#[test]
+fn validate_default_bindings() {
+    let input_map = InputMap::default();
+    for action in InputAction::iter() {
+        assert!(
+            !input_map.bindings(action).is_empty(),
+            "{action} has no default binding"
+        );
+    }
+}
+
+#[test]
+fn validate_input_map_roundtrip() {
+    let input_map = InputMap::default();
+    let contents = ron::to_string(&input_map).unwrap();
+    let loaded: InputMap = ron::from_str(&contents).unwrap();
+    for action in InputAction::iter() {
+        assert_eq!(input_map.bindings(action), loaded.bindings(action));
+    }
+}
+
+#[test]
+fn validate_rebind_keeps_other_devices() {
+    let mut input_map = InputMap::default();
+    input_map.rebind(InputAction::Select, Binding::Key(KeyCode::KeyQ));
+
+    assert_eq!(
+        input_map.primary_key(InputAction::Select),
+        Some(KeyCode::KeyQ)
+    );
+    assert_eq!(
+        input_map.primary_gamepad_button(InputAction::Select),
+        Some(GamepadButtonType::West)
+    );
+    assert!(!input_map
+        .bindings(InputAction::Select)
+        .contains(&Binding::Key(KeyCode::KeyE)));
+}
+
+#[test]
+fn validate_recording_roundtrip() {
+    let mut recording = InputRecording::new(7);
+    recording.frames.push(PlayerInput {
+        move_direction: Vec2::new(1.0, 0.0),
+        select_socket: true,
+        ..default()
+    });
+    recording.frames.push(PlayerInput::default());
+
+    let contents = recording.to_ron().unwrap();
+    assert_eq!(InputRecording::from_ron(&contents).unwrap(), recording);
+}
+
+#[test]
+fn validate_seeded_rng_is_deterministic() {
+    let mut first = GameRng::seeded(7);
+    let mut second = GameRng::seeded(7);
+    for _ in 0..16 {
+        assert_eq!(first.gen::<u64>(), second.gen::<u64>());
+    }
+}
+
+#[test]
+fn validate_replay_mode_from_args() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    assert!(matches!(
+        ReplayMode::from_args(args(&[]).into_iter()),
+        ReplayMode::Off
+    ));
+    match ReplayMode::from_args(args(&["--record", "bug.ron"]).into_iter()) {
+        ReplayMode::Recording { file, recording } => {
+            assert_eq!(file, "bug.ron");
+            assert!(recording.frames.is_empty());
+        }
+        mode => panic!("expected to record, got {:?}", mode),
+    }
+}
+
+#[test]
+fn validate_replay_mode_from_replay_args() {
+    let args = ["--replay", "bug.ron"].map(|arg| arg.to_string());
+    let mut recording = InputRecording::new(7);
+    recording.frames.push(PlayerInput {
+        select_socket: true,
+        ..default()
+    });
+    let contents = recording.to_ron().unwrap();
+
+    let read = |file: &str| (file == "bug.ron").then(|| contents.clone());
+    match ReplayMode::parse_args(args.clone().into_iter(), read) {
+        ReplayMode::Replaying {
+            recording: replayed,
+            frame,
+        } => {
+            assert_eq!(replayed, recording);
+            assert_eq!(frame, 0);
+        }
+        mode => panic!("expected to replay, got {:?}", mode),
+    }
+}
+
+#[test]
+fn reject_missing_or_invalid_replay() {
+    let args = ["--replay", "bug.ron"].map(|arg| arg.to_string());
+    assert!(matches!(
+        ReplayMode::parse_args(args.clone().into_iter(), |_| None),
+        ReplayMode::Off
+    ));
+    assert!(matches!(
+        ReplayMode::parse_args(args.into_iter(), |_| Some("not a recording".to_string())),
+        ReplayMode::Off
+    ));
+}
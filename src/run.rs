@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+
+use crate::{GameState, PauseState};
+
+/// Marks entities that only live for a single run.
+/// They get despawned as soon as a new run starts or we go back to the title screen.
+#[derive(Component)]
+pub struct RunScoped;
+
+/// Send this to throw away the current run and start again from the intro.
+#[derive(Event)]
+pub struct RestartRun;
+
+/// Put `R` back into its initial state when a new run starts.
+pub fn reset_resource<R: Resource + Default>(mut resource: ResMut<R>) {
+    *resource = R::default();
+}
+
+fn restart_run(
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    next_game_state.set(GameState::Intro);
+    next_pause_state.set(PauseState::Running);
+}
+
+fn despawn_run_entities(mut commands: Commands, q_entities: Query<Entity, With<RunScoped>>) {
+    for entity in &q_entities {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RestartRun>()
+            .add_systems(Update, restart_run.run_if(on_event::<RestartRun>()))
+            .add_systems(OnEnter(GameState::Intro), despawn_run_entities)
+            .add_systems(OnEnter(GameState::Title), despawn_run_entities);
+    }
+}
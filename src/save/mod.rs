@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod test;
+mod unlocks;
+
+pub use unlocks::Unlocks;
+
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::LevelSelection;
+use bevy_yarnspinner::events::DialogueCompleteEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aspect::{Aspect, Combiner, Socket, SocketMemory},
+    player::Player,
+    run::RestartRun,
+    ui::dialogue::{
+        runner::TimeSinceGaming,
+        variables::{DialogueVariables, SavedVariable},
+    },
+    utils::storage,
+    world::{Bed, CurrentLevel, TutorialProgress},
+    GameState,
+};
+
+const SAVE_FILE: &str = "save.ron";
+// Increase this whenever `SaveData` changes in an incompatible way.
+const SAVE_VERSION: u32 = 2;
+
+/// Send this to write the current progress to disk.
+#[derive(Event)]
+pub struct SaveGame;
+/// Sent once a save file was fully applied to the world.
+#[derive(Event)]
+pub struct GameLoaded;
+
+#[derive(Serialize, Deserialize)]
+struct SocketData {
+    level: String,
+    position: [i32; 2],
+    aspect: Aspect,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveData {
+    version: u32,
+    level: String,
+    sockets: Vec<SocketData>,
+    combiner: Combiner,
+    tutorial: TutorialProgress,
+    time_since_gaming: f32,
+    bed_used: bool,
+    player_position: [f32; 2],
+    /// Variables set from within yarn.
+    #[serde(default)]
+    variables: HashMap<String, SavedVariable>,
+}
+
+#[derive(Resource)]
+struct PendingSave(SaveData);
+
+pub fn delete_save() {
+    storage::remove(SAVE_FILE);
+}
+
+/// Whether there is a save file that can be continued from.
+pub fn save_exists() -> bool {
+    read_save().is_some()
+}
+
+fn read_save() -> Option<SaveData> {
+    let contents = storage::read(SAVE_FILE)?;
+    let data: SaveData = match ron::from_str(&contents) {
+        Ok(r) => r,
+        Err(err) => {
+            error!("failed to parse save file, {}", err);
+            return None;
+        }
+    };
+
+    if data.version != SAVE_VERSION {
+        warn!(
+            "save file has version {}, but only version {} is supported, ignoring it",
+            data.version, SAVE_VERSION
+        );
+        return None;
+    }
+    Some(data)
+}
+
+fn save_game(
+    current_level: Res<CurrentLevel>,
+    socket_memory: Res<SocketMemory>,
+    combiner: Res<Combiner>,
+    tutorial_progress: Res<TutorialProgress>,
+    time_since_gaming: Res<TimeSinceGaming>,
+    dialogue_variables: Res<DialogueVariables>,
+    q_sockets: Query<(&Transform, &Socket)>,
+    q_player: Query<&Transform, With<Player>>,
+    q_bed: Query<&Bed>,
+) {
+    let player_transform = match q_player.get_single() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    let sockets = socket_memory.merged(&current_level.0, q_sockets.iter());
+
+    let data = SaveData {
+        version: SAVE_VERSION,
+        level: current_level.0.clone(),
+        sockets: sockets
+            .into_iter()
+            .map(|((level, position), aspect)| SocketData {
+                level,
+                position,
+                aspect,
+            })
+            .collect(),
+        combiner: combiner.clone(),
+        tutorial: *tutorial_progress,
+        time_since_gaming: time_since_gaming.0.elapsed_secs(),
+        bed_used: q_bed.iter().any(|bed| bed.used),
+        player_position: player_transform.translation.truncate().to_array(),
+        variables: dialogue_variables.saved_variables(),
+    };
+
+    write_save(&data);
+}
+
+fn write_save(data: &SaveData) {
+    let contents = match ron::ser::to_string_pretty(data, ron::ser::PrettyConfig::default()) {
+        Ok(r) => r,
+        Err(err) => {
+            error!("failed to serialize save data, {}", err);
+            return;
+        }
+    };
+    if let Err(err) = storage::write(SAVE_FILE, &contents) {
+        error!("failed to write save file, {}", err);
+    }
+}
+
+/// Restore everything that has to be known before the world spawns.
+/// The rest gets applied in `apply_save` once the player exists.
+fn load_save(
+    mut commands: Commands,
+    mut tutorial_progress: ResMut<TutorialProgress>,
+    mut time_since_gaming: ResMut<TimeSinceGaming>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut level_selection: ResMut<LevelSelection>,
+    mut socket_memory: ResMut<SocketMemory>,
+    dialogue_variables: Res<DialogueVariables>,
+) {
+    let data = match read_save() {
+        Some(r) => r,
+        None => return,
+    };
+
+    // Sockets pick up their saved aspects from the memory when they spawn.
+    socket_memory.0 = data
+        .sockets
+        .iter()
+        .map(|socket| ((socket.level.clone(), socket.position), socket.aspect))
+        .collect();
+    *level_selection = LevelSelection::identifier(&data.level);
+    current_level.0 = data.level.clone();
+
+    *tutorial_progress = data.tutorial;
+    dialogue_variables.load_saved_variables(&data.variables);
+    time_since_gaming
+        .0
+        .set_elapsed(Duration::from_secs_f32(data.time_since_gaming));
+    commands.insert_resource(PendingSave(data));
+}
+
+fn apply_save(
+    mut commands: Commands,
+    pending_save: Res<PendingSave>,
+    mut combiner: ResMut<Combiner>,
+    mut q_player: Query<&mut Transform, With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_game_loaded: EventWriter<GameLoaded>,
+) {
+    // The player spawns together with the level, so once it's there the sockets are too.
+    let mut player_transform = match q_player.get_single_mut() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    let data = &pending_save.0;
+    *combiner = data.combiner.clone();
+
+    let position = Vec2::from_array(data.player_position);
+    player_transform.translation = position.extend(player_transform.translation.z);
+
+    // The final dialogue always ends in the ending, so that's where a run with a used bed continues.
+    // The sockets and combinations are restored by now, the narrator picks the ending from them.
+    if data.bed_used {
+        next_state.set(GameState::Ending);
+    }
+    ev_game_loaded.send(GameLoaded);
+    commands.remove_resource::<PendingSave>();
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(unlocks::UnlocksPlugin)
+            .add_event::<SaveGame>()
+            .add_event::<GameLoaded>()
+            .add_systems(OnEnter(GameState::Gaming), load_save)
+            .add_systems(
+                Update,
+                (
+                    apply_save.run_if(resource_exists::<PendingSave>),
+                    save_game
+                        .run_if(on_event::<SaveGame>().or_else(on_event::<DialogueCompleteEvent>()))
+                        .run_if(not(resource_exists::<PendingSave>)),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Gaming)),
+            )
+            // The run is over, next time the game should start from the beginning.
+            .add_systems(OnEnter(GameState::Ending), delete_save)
+            .add_systems(Update, delete_save.run_if(on_event::<RestartRun>()));
+    }
+}
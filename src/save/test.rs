@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+use crate::{
+    aspect::{Aspect, AspectPlugin, Combiner},
+    harness::{grid_position, TestApp},
+    world::{CurrentLevel, MapBedPlugin, TutorialProgress},
+    GameState,
+};
+
+use super::{write_save, SaveData, SavePlugin, SocketData, SAVE_VERSION};
+
+/// A run that was saved after joy and sadness were combined and the bed was used.
+fn bed_used_save() -> SaveData {
+    let level = CurrentLevel::default().0;
+    let socket = |position: [i32; 2], aspect: Aspect| SocketData {
+        level: level.clone(),
+        position,
+        aspect,
+    };
+    SaveData {
+        version: SAVE_VERSION,
+        level: level.clone(),
+        sockets: vec![
+            socket([0, 4], Aspect::Joy),
+            socket([2, 4], Aspect::Nostalgia),
+            socket([0, 0], Aspect::Sadness),
+            socket([2, 0], Aspect::Nostalgia),
+        ],
+        combiner: Combiner {
+            last_combined_aspect: Aspect::Nostalgia,
+            all_sockets_full: true,
+            combined_aspects: vec![Aspect::Nostalgia],
+            ..default()
+        },
+        tutorial: TutorialProgress::default(),
+        time_since_gaming: 60.0,
+        bed_used: true,
+        player_position: [0.0, 0.0],
+        variables: default(),
+    }
+}
+
+#[test]
+fn validate_used_bed_continues_with_ending() {
+    write_save(&bed_used_save());
+
+    let mut app = TestApp::headless().with_plugins((AspectPlugin, MapBedPlugin, SavePlugin));
+    app.enter_state(GameState::Gaming);
+    // The level spawns with the sockets as they are in LDtk, the save fills them back in.
+    app.spawn_socket(Aspect::Joy, true, 0, 4);
+    app.spawn_socket(Aspect::NotImplemented, true, 2, 4);
+    app.spawn_socket(Aspect::Sadness, false, 0, 0);
+    app.spawn_socket(Aspect::NotImplemented, false, 2, 0);
+    app.spawn_combiner(5, 2);
+    app.spawn_player(grid_position(10, 10));
+    app.update();
+    app.update();
+
+    assert_eq!(app.resource::<State<GameState>>().get(), &GameState::Ending);
+    assert_eq!(
+        app.socket_aspects(true),
+        vec![Aspect::Joy, Aspect::Nostalgia]
+    );
+    assert_eq!(
+        app.socket_aspects(false),
+        vec![Aspect::Sadness, Aspect::Nostalgia]
+    );
+    assert_eq!(
+        app.resource::<Combiner>().combined_aspects,
+        vec![Aspect::Nostalgia]
+    );
+}
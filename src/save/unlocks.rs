@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aspect::{Aspect, CombinedAspect, Combiner},
+    npc::narrator::{NarratorDialogue, TriggeredNarratorDialogue},
+    utils::storage,
+    GameState,
+};
+
+const UNLOCKS_FILE: &str = "unlocks.ron";
+
+/// The endings and combinations the player has seen in any run.
+/// Unlike the save file this is never deleted, it's what the gallery shows.
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct Unlocks {
+    endings: HashSet<String>,
+    combinations: HashSet<Aspect>,
+}
+
+impl Unlocks {
+    pub fn has_ending(&self, node: &str) -> bool {
+        self.endings.contains(node)
+    }
+
+    pub fn has_combination(&self, aspect: Aspect) -> bool {
+        self.combinations.contains(&aspect)
+    }
+
+    /// Load the unlocks from disk, nothing is unlocked if there are none yet.
+    pub fn load() -> Self {
+        let contents = match storage::read(UNLOCKS_FILE) {
+            Some(r) => r,
+            None => return Self::default(),
+        };
+
+        match ron::from_str::<Self>(&contents) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to parse unlocks, starting without any, {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to serialize unlocks, {}", err);
+                return;
+            }
+        };
+        if let Err(err) = storage::write(UNLOCKS_FILE, &contents) {
+            error!("failed to write unlocks, {}", err);
+        }
+    }
+}
+
+fn unlock_endings(
+    mut unlocks: ResMut<Unlocks>,
+    mut ev_triggered_narrator_dialogue: EventReader<TriggeredNarratorDialogue>,
+) {
+    let mut unlocked = false;
+    for ev in ev_triggered_narrator_dialogue.read() {
+        if ev.0 == NarratorDialogue::intro() {
+            continue;
+        }
+        unlocked |= unlocks.endings.insert(ev.0.node().to_string());
+    }
+    if unlocked {
+        unlocks.save();
+    }
+}
+
+fn unlock_combinations(mut unlocks: ResMut<Unlocks>, combiner: Res<Combiner>) {
+    let aspect = combiner.last_combined_aspect;
+    if aspect == Aspect::NotImplemented {
+        return;
+    }
+    if unlocks.combinations.insert(aspect) {
+        unlocks.save();
+    }
+}
+
+pub struct UnlocksPlugin;
+
+impl Plugin for UnlocksPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Unlocks::load()).add_systems(
+            Update,
+            (
+                unlock_endings.run_if(in_state(GameState::Ending)),
+                unlock_combinations
+                    .run_if(on_event::<CombinedAspect>())
+                    .run_if(in_state(GameState::Gaming)),
+            ),
+        );
+    }
+}
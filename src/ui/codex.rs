@@ -0,0 +1,387 @@
+use bevy::{color::palettes::css::GRAY, input::InputSystem, prelude::*};
+use strum::IntoEnumIterator;
+
+use crate::{
+    aspect::{icon_texture, Aspect, AspectRecipes},
+    locale::Locale,
+    npc::narrator::evaluate_aspect,
+    player::input_map::{InputAction, InputDevice, InputDevices, InputMap},
+    save::Unlocks,
+    GameAssets, GameState,
+};
+
+const BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.9);
+const ICON_SIZE: f32 = 128.0;
+
+#[derive(Resource, Default)]
+pub struct CodexMenu {
+    open: bool,
+    selected: usize,
+}
+
+impl CodexMenu {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.selected = 0;
+    }
+}
+
+/// Same as with the settings, menus below only take input again once the codex is gone.
+pub fn codex_menu_closed(menu: Res<CodexMenu>, q_screens: Query<(), With<CodexScreen>>) -> bool {
+    !menu.is_open() && q_screens.is_empty()
+}
+
+#[derive(Component)]
+pub struct CodexScreen;
+
+#[derive(Component)]
+struct CodexButton {
+    index: usize,
+}
+
+#[derive(Component)]
+enum CodexDetail {
+    Icon,
+    Name,
+    Recipe,
+    Leaning,
+    Description,
+}
+
+/// Every aspect the codex has an entry for.
+fn codex_aspects() -> Vec<Aspect> {
+    Aspect::iter()
+        .filter(|aspect| *aspect != Aspect::NotImplemented)
+        .collect()
+}
+
+fn description(locale: &Locale, aspect: Aspect) -> String {
+    locale.text(&format!("codex.description.{}", aspect))
+}
+
+/// A qualitative take on how much an aspect pulls towards either side.
+fn leaning(locale: &Locale, aspect: Aspect) -> String {
+    let key = match evaluate_aspect(aspect) {
+        i32::MIN..=-4 => "codex.leaning.strongly_dark",
+        -3..=-1 => "codex.leaning.dark",
+        0 => "codex.leaning.balanced",
+        1..=3 => "codex.leaning.light",
+        _ => "codex.leaning.strongly_light",
+    };
+    locale.text(key)
+}
+
+fn recipe_text(recipes: &AspectRecipes, locale: &Locale, aspect: Aspect) -> String {
+    let ingredients = recipes
+        .recipes()
+        .iter()
+        .filter(|recipe| recipe.result == aspect)
+        .map(|recipe| {
+            format!(
+                "{} + {}",
+                locale.aspect_name(recipe.left),
+                locale.aspect_name(recipe.right)
+            )
+        })
+        .collect::<Vec<String>>();
+    if ingredients.is_empty() {
+        locale.text("codex.base_aspect")
+    } else {
+        ingredients.join(&locale.text("codex.recipe_or"))
+    }
+}
+
+/// Base aspects are always known, the others only once they were combined in any run.
+fn is_unlocked(recipes: &AspectRecipes, unlocks: &Unlocks, aspect: Aspect) -> bool {
+    let is_base = !recipes
+        .recipes()
+        .iter()
+        .any(|recipe| recipe.result == aspect);
+    is_base || unlocks.has_combination(aspect)
+}
+
+fn text_style(locale: &Res<Locale>, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: locale.font(),
+        font_size,
+        color,
+    }
+}
+
+/// Same as the gallery, the hint follows the bindings of the device the player uses.
+fn footer_text(locale: &Locale, input_map: &InputMap, input_device: InputDevice) -> String {
+    locale
+        .text("codex.footer")
+        .replace(
+            "{up}",
+            &input_map.hint(InputAction::DialogueUp, input_device),
+        )
+        .replace(
+            "{down}",
+            &input_map.hint(InputAction::DialogueDown, input_device),
+        )
+        .replace(
+            "{escape}",
+            &input_map.hint(InputAction::Escape, input_device),
+        )
+}
+
+fn spawn_menu(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    input_map: Res<InputMap>,
+    input_device: Res<InputDevice>,
+    recipes: Res<AspectRecipes>,
+    unlocks: Res<Unlocks>,
+) {
+    let mut entries = Vec::new();
+    for (index, aspect) in codex_aspects().into_iter().enumerate() {
+        let name = if is_unlocked(&recipes, &unlocks, aspect) {
+            locale.aspect_name(aspect)
+        } else {
+            locale.text("ui.locked")
+        };
+        let text = commands
+            .spawn((
+                TextBundle::from_section(name, text_style(&locale, 36.0, GRAY.into())),
+                Label,
+            ))
+            .id();
+        entries.push(
+            commands
+                .spawn((CodexButton { index }, ButtonBundle::default()))
+                .add_child(text)
+                .id(),
+        );
+    }
+    let list = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&entries)
+        .id();
+
+    let icon = commands
+        .spawn((
+            CodexDetail::Icon,
+            ImageBundle {
+                style: Style {
+                    width: Val::Px(ICON_SIZE),
+                    height: Val::Px(ICON_SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .id();
+    let mut details = vec![icon];
+    for (detail, font_size, color) in [
+        (CodexDetail::Name, 60.0, Color::WHITE),
+        (CodexDetail::Recipe, 32.0, GRAY.into()),
+        (CodexDetail::Leaning, 32.0, GRAY.into()),
+        (CodexDetail::Description, 32.0, Color::WHITE),
+    ] {
+        details.push(
+            commands
+                .spawn((
+                    detail,
+                    TextBundle::from_section("", text_style(&locale, font_size, color))
+                        .with_text_justify(JustifyText::Center),
+                ))
+                .id(),
+        );
+    }
+    let detail_panel = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(45.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&details)
+        .id();
+
+    let body = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(80.0),
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&[list, detail_panel])
+        .id();
+
+    let header = commands
+        .spawn(TextBundle::from_section(
+            locale.text("menu.codex"),
+            text_style(&locale, 80.0, Color::WHITE),
+        ))
+        .id();
+    let footer = commands
+        .spawn(TextBundle::from_section(
+            footer_text(&locale, &input_map, *input_device),
+            text_style(&locale, 30.0, GRAY.into()),
+        ))
+        .id();
+
+    commands
+        .spawn((
+            CodexScreen,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(450),
+                ..default()
+            },
+        ))
+        .push_children(&[header, body, footer]);
+}
+
+fn despawn_menu(mut commands: Commands, q_screens: Query<Entity, With<CodexScreen>>) {
+    for entity in &q_screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_menu(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut menu: ResMut<CodexMenu>,
+    q_buttons: Query<(&Interaction, &CodexButton), Changed<Interaction>>,
+) {
+    if input_map.just_pressed(InputAction::Escape, &devices) {
+        menu.open = false;
+        return;
+    }
+
+    for (interaction, button) in &q_buttons {
+        if *interaction != Interaction::None {
+            menu.selected = button.index;
+        }
+    }
+
+    let len = codex_aspects().len();
+    if input_map.just_pressed(InputAction::DialogueUp, &devices) {
+        menu.selected = (menu.selected + len - 1) % len;
+    }
+    if input_map.just_pressed(InputAction::DialogueDown, &devices) {
+        menu.selected = (menu.selected + 1) % len;
+    }
+}
+
+fn update_details(
+    assets: Res<GameAssets>,
+    locale: Res<Locale>,
+    recipes: Res<AspectRecipes>,
+    unlocks: Res<Unlocks>,
+    menu: Res<CodexMenu>,
+    q_buttons: Query<(&CodexButton, &Children)>,
+    mut q_details: Query<(&CodexDetail, Option<&mut Text>, Option<&mut UiImage>)>,
+    mut q_texts: Query<&mut Text, Without<CodexDetail>>,
+) {
+    for (button, children) in &q_buttons {
+        let color = if button.index == menu.selected {
+            Color::WHITE
+        } else {
+            GRAY.into()
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = q_texts.get_mut(*child) {
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+
+    let aspect = match codex_aspects().get(menu.selected) {
+        Some(r) => *r,
+        None => return,
+    };
+    let unlocked = is_unlocked(&recipes, &unlocks, aspect);
+
+    for (detail, text, image) in &mut q_details {
+        if let Some(mut image) = image {
+            image.texture = if unlocked {
+                icon_texture(&assets, &aspect)
+            } else {
+                assets.placeholder_icon.clone()
+            };
+        }
+        let value = match (detail, unlocked) {
+            (CodexDetail::Icon, _) => continue,
+            (CodexDetail::Name, true) => locale.aspect_name(aspect),
+            (CodexDetail::Recipe, true) => recipe_text(&recipes, &locale, aspect),
+            (CodexDetail::Leaning, true) => leaning(&locale, aspect),
+            (CodexDetail::Description, true) => description(&locale, aspect),
+            (CodexDetail::Description, false) => locale.text("codex.locked_hint"),
+            (_, false) => locale.text("ui.locked"),
+        };
+        if let Some(mut text) = text {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// The menu only reacts to input once it's on screen,
+/// so that the press that opened it doesn't also act in it.
+fn menu_shown(menu: Res<CodexMenu>, q_screens: Query<(), With<CodexScreen>>) -> bool {
+    menu.is_open() && !q_screens.is_empty()
+}
+
+fn menu_opened(menu: Res<CodexMenu>, q_screens: Query<(), With<CodexScreen>>) -> bool {
+    menu.is_open() && q_screens.is_empty()
+}
+
+fn menu_closed(menu: Res<CodexMenu>, q_screens: Query<(), With<CodexScreen>>) -> bool {
+    !menu.is_open() && !q_screens.is_empty()
+}
+
+fn menu_spawned(q_screens: Query<(), Added<CodexScreen>>) -> bool {
+    !q_screens.is_empty()
+}
+
+pub struct CodexPlugin;
+
+impl Plugin for CodexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CodexMenu>()
+            .add_systems(
+                PreUpdate,
+                navigate_menu.after(InputSystem).run_if(menu_shown),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_menu.run_if(menu_opened),
+                    despawn_menu.run_if(menu_closed),
+                    update_details.run_if(resource_changed::<CodexMenu>.or_else(menu_spawned)),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
@@ -0,0 +1,171 @@
+//! Ways to get through dialogue without pressing continue on every line.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_yarnspinner::{events::*, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{utils::storage, GameState};
+
+use super::log::dialogue_log_closed;
+use super::spawn::DialogueContinueNode;
+use super::typewriter::Typewriter;
+use super::updating::continue_runners;
+use super::DialogueViewSystemSet;
+
+const CONFIG_FILE: &str = "dialogue.ron";
+const SEEN_LINES_FILE: &str = "seen_lines.ron";
+/// Seconds to wait after a line is fully written before auto advance continues.
+const AUTO_ADVANCE_DELAY: f32 = 1.0;
+/// Additional seconds per grapheme, longer lines take longer to read.
+const AUTO_ADVANCE_DELAY_PER_GRAPHEME: f32 = 0.04;
+/// Seen lines still flash by, so that skipping doesn't look like the dialogue broke.
+const SKIP_DELAY: f32 = 0.1;
+
+/// The dialogue options from the settings, stored next to the other configs.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DialogueSettings {
+    /// Continue on its own once the reader had some time for the line.
+    pub auto_advance: bool,
+    /// Rush through lines that were already read, stops at options and new lines.
+    pub skip_seen_lines: bool,
+}
+
+impl DialogueSettings {
+    pub fn load() -> Self {
+        let contents = match storage::read(CONFIG_FILE) {
+            Some(r) => r,
+            None => return Self::default(),
+        };
+
+        match ron::from_str::<Self>(&contents) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to parse dialogue config, using defaults, {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to serialize dialogue config, {}", err);
+                return;
+            }
+        };
+        if let Err(err) = storage::write(CONFIG_FILE, &contents) {
+            error!("failed to write dialogue config, {}", err);
+        }
+    }
+}
+
+/// The yarn line IDs that were presented in any run.
+/// Like the unlocks this is never deleted.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct SeenLines {
+    lines: HashSet<String>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl SeenLines {
+    /// Mark the line as seen, returns whether it was seen before.
+    pub fn see(&mut self, line: &LocalizedLine) -> bool {
+        let seen = !self.lines.insert(line.id.0.clone());
+        self.dirty |= !seen;
+        seen
+    }
+
+    pub fn load() -> Self {
+        let contents = match storage::read(SEEN_LINES_FILE) {
+            Some(r) => r,
+            None => return Self::default(),
+        };
+
+        match ron::from_str::<Self>(&contents) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to parse seen lines, starting without any, {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        let contents = match ron::ser::to_string(self) {
+            Ok(r) => r,
+            Err(err) => {
+                error!("failed to serialize seen lines, {}", err);
+                return;
+            }
+        };
+        match storage::write(SEEN_LINES_FILE, &contents) {
+            Ok(()) => self.dirty = false,
+            Err(err) => error!("failed to write seen lines, {}", err),
+        }
+    }
+}
+
+fn auto_advance_delay(line_length: usize) -> f32 {
+    AUTO_ADVANCE_DELAY + line_length as f32 * AUTO_ADVANCE_DELAY_PER_GRAPHEME
+}
+
+fn auto_continue_dialogue(
+    time: Res<Time>,
+    settings: Res<DialogueSettings>,
+    typewriter: Res<Typewriter>,
+    mut q_dialogue_runners: Query<&mut DialogueRunner>,
+    mut q_continue_visibility: Query<&mut Visibility, With<DialogueContinueNode>>,
+    mut waited: Local<f32>,
+) {
+    if !typewriter.is_finished() {
+        *waited = 0.0;
+        return;
+    }
+
+    let delay = if typewriter.is_skipping_seen_line() {
+        SKIP_DELAY
+    } else if settings.auto_advance {
+        auto_advance_delay(typewriter.line_length())
+    } else {
+        return;
+    };
+
+    *waited += time.delta_seconds();
+    if *waited < delay {
+        return;
+    }
+    *waited = 0.0;
+    continue_runners(&mut q_dialogue_runners, &mut q_continue_visibility);
+}
+
+/// Writing on every line would be a lot of disk access, once per dialogue is enough.
+fn save_seen_lines(mut seen_lines: ResMut<SeenLines>) {
+    seen_lines.save();
+}
+
+pub struct DialogueAdvancePlugin;
+
+impl Plugin for DialogueAdvancePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DialogueSettings::load())
+            .insert_resource(SeenLines::load())
+            .add_systems(
+                Update,
+                (
+                    // Nothing should move on while the player reads through the log.
+                    auto_continue_dialogue.run_if(dialogue_log_closed),
+                    save_seen_lines.run_if(on_event::<DialogueCompleteEvent>()),
+                )
+                    .after(YarnSpinnerSystemSet)
+                    .in_set(DialogueViewSystemSet)
+                    .run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
@@ -1,8 +1,10 @@
-use std::str::FromStr;
-
 use bevy::prelude::*;
 
-use crate::{audio::PlaySound, npc::NpcDialogue, GameAssets, GameState};
+use crate::{
+    audio::{AudioBus, PlaySound},
+    npc::characters::Characters,
+    GameState,
+};
 
 #[derive(Event)]
 pub struct PlayBlipEvent {
@@ -17,50 +19,27 @@ impl PlayBlipEvent {
     }
 }
 
-fn character_sound(assets: &Res<GameAssets>, character: &str) -> PlaySound {
-    let character = character.trim_start_matches('_');
-    // Narrator, i.e. no character name on screen.
-    if character.is_empty() {
-        return PlaySound {
-            volume: 0.0,
-            ..default()
-        };
-    }
-
-    match NpcDialogue::from_str(character) {
-        Ok(r) => match r {
-            NpcDialogue::Ami => PlaySound {
-                clip: assets.ami_blip_sound.clone(),
-                playback_rate: 0.9,
-                rand_speed_intensity: 0.01,
-                ..default()
-            },
-            NpcDialogue::Ima => PlaySound {
-                clip: assets.ima_blip_sound.clone(),
-                playback_rate: 0.8,
-                rand_speed_intensity: 0.01,
-                ..default()
-            },
-        },
-        Err(_) => {
-            if character == "???" {
-                error!("You should never hardcode character name: '???' in dialogues!");
-            }
-            PlaySound {
-                clip: assets.ima_blip_sound.clone(),
-                ..default()
-            }
-        }
-    }
+/// The blip of the character, `None` for speakers that write silently, e.g. the narrator.
+fn character_sound(characters: &Characters, character: &str) -> Option<PlaySound> {
+    let profile = characters.speaker(Some(character));
+    Some(PlaySound {
+        clip: profile.blip.clone()?,
+        playback_rate: profile.playback_rate,
+        rand_speed_intensity: profile.pitch_range,
+        bus: AudioBus::Blip,
+        ..default()
+    })
 }
 
 fn play_blips(
-    assets: Res<GameAssets>,
+    characters: Res<Characters>,
     mut ev_play_blip: EventReader<PlayBlipEvent>,
     mut ev_play_sound: EventWriter<PlaySound>,
 ) {
     for ev in ev_play_blip.read() {
-        ev_play_sound.send(character_sound(&assets, &ev.dialogue));
+        if let Some(sound) = character_sound(&characters, &ev.dialogue) {
+            ev_play_sound.send(sound);
+        }
     }
 }
 
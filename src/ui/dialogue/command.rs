@@ -1,11 +1,164 @@
+use std::str::FromStr;
+
 use bevy::prelude::*;
+use bevy_kira_audio::AudioSource;
+use bevy_yarnspinner::prelude::YarnCommands;
+use strum_macros::EnumString;
+
+use crate::{
+    audio::{BgmTrack, PlaySound, SetBgm},
+    player::{DespawnIma, SpawnIma},
+    ui::FadeScreen,
+    world::camera::CameraShake,
+    GameAssets, GameState,
+};
+
+const COMMAND_FADE_DURATION: f32 = 1.0;
+
+/// A command writers can use in yarn files, e.g. `<<camera_shake 0.5>>`.
+pub struct DialogueCommand {
+    pub name: &'static str,
+    /// Only checked by the yarn tests, Yarn Spinner itself doesn't know about it.
+    #[allow(dead_code)]
+    pub args: usize,
+    /// `None` for commands Yarn Spinner ships itself.
+    register: Option<fn(&mut YarnCommands, &'static str)>,
+}
+
+const fn command(
+    name: &'static str,
+    args: usize,
+    register: fn(&mut YarnCommands, &'static str),
+) -> DialogueCommand {
+    DialogueCommand {
+        name,
+        args,
+        register: Some(register),
+    }
+}
 
-use crate::GameState;
+/// All commands available in yarn files, the yarn tests validate against this.
+pub const YARN_COMMANDS: [DialogueCommand; 10] = [
+    command("trigger_ending", 0, |c, name| {
+        c.add_command(name, trigger_ending_command);
+    }),
+    command("game_over", 0, |c, name| {
+        c.add_command(name, trigger_game_over_command);
+    }),
+    command("camera_shake", 1, |c, name| {
+        c.add_command(name, camera_shake_command);
+    }),
+    command("play_sound", 1, |c, name| {
+        c.add_command(name, play_sound_command);
+    }),
+    command("fade_out", 0, |c, name| {
+        c.add_command(name, fade_out_command);
+    }),
+    command("fade_in", 0, |c, name| {
+        c.add_command(name, fade_in_command);
+    }),
+    DialogueCommand {
+        name: "wait",
+        args: 1,
+        register: None,
+    },
+    command("set_bgm", 1, |c, name| {
+        c.add_command(name, set_bgm_command);
+    }),
+    command("spawn_ima", 0, |c, name| {
+        c.add_command(name, spawn_ima_command);
+    }),
+    command("despawn_ima", 0, |c, name| {
+        c.add_command(name, despawn_ima_command);
+    }),
+];
 
-pub fn trigger_ending_command(In(()): In<()>, mut next_state: ResMut<NextState<GameState>>) {
+/// The sounds `play_sound` accepts, e.g. `<<play_sound koto_hit>>`.
+#[derive(Clone, Copy, PartialEq, Debug, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum YarnSound {
+    KotoHit,
+    Footstep,
+    SelectAspect,
+    DeselectAspect,
+}
+
+impl YarnSound {
+    fn clip(&self, assets: &GameAssets) -> Handle<AudioSource> {
+        match self {
+            YarnSound::KotoHit => assets.koto_hit_sound.clone(),
+            YarnSound::Footstep => assets.footstep.clone(),
+            YarnSound::SelectAspect => assets.select_aspect.clone(),
+            YarnSound::DeselectAspect => assets.deselect_aspect.clone(),
+        }
+    }
+}
+
+pub fn register_commands(commands: &mut YarnCommands) {
+    for command in &YARN_COMMANDS {
+        if let Some(register) = command.register {
+            register(commands, command.name);
+        }
+    }
+}
+
+fn trigger_ending_command(In(()): In<()>, mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(GameState::Ending);
 }
 
-pub fn trigger_game_over_command(In(()): In<()>, mut next_state: ResMut<NextState<GameState>>) {
+fn trigger_game_over_command(In(()): In<()>, mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(GameState::GameOver);
 }
+
+fn camera_shake_command(In(trauma): In<f32>, mut shake: ResMut<CameraShake>) {
+    shake.add_trauma(trauma);
+}
+
+fn play_sound_command(
+    In(name): In<String>,
+    assets: Res<GameAssets>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+) {
+    let sound = match YarnSound::from_str(&name) {
+        Ok(r) => r,
+        Err(err) => {
+            error!("not a valid sound in yarn command, '{}', {}", name, err);
+            return;
+        }
+    };
+    ev_play_sound.send(PlaySound {
+        clip: sound.clip(&assets),
+        ..default()
+    });
+}
+
+fn fade_out_command(In(()): In<()>, mut ev_fade_screen: EventWriter<FadeScreen>) {
+    ev_fade_screen.send(FadeScreen {
+        to_black: true,
+        duration: COMMAND_FADE_DURATION,
+    });
+}
+
+fn fade_in_command(In(()): In<()>, mut ev_fade_screen: EventWriter<FadeScreen>) {
+    ev_fade_screen.send(FadeScreen {
+        to_black: false,
+        duration: COMMAND_FADE_DURATION,
+    });
+}
+
+fn set_bgm_command(In(name): In<String>, mut ev_set_bgm: EventWriter<SetBgm>) {
+    match BgmTrack::from_str(&name) {
+        Ok(track) => {
+            ev_set_bgm.send(SetBgm(track));
+        }
+        Err(err) => error!("not a valid bgm track in yarn command, '{}', {}", name, err),
+    }
+}
+
+fn spawn_ima_command(In(()): In<()>, mut ev_spawn_ima: EventWriter<SpawnIma>) {
+    ev_spawn_ima.send(SpawnIma);
+}
+
+fn despawn_ima_command(In(()): In<()>, mut ev_despawn_ima: EventWriter<DespawnIma>) {
+    ev_despawn_ima.send(DespawnIma);
+}
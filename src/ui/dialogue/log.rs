@@ -0,0 +1,360 @@
+//! Every line that was presented in the current run, so that lines that were skipped too fast can be reread.
+
+use bevy::{
+    color::palettes::css::GRAY,
+    input::{mouse::MouseWheel, InputSystem},
+    prelude::*,
+};
+use bevy_yarnspinner::{events::*, prelude::*};
+
+use crate::{
+    locale::{Locale, LocalizedText},
+    npc::characters::Characters,
+    player::{
+        input::PlayerInput,
+        input_map::{InputAction, InputDevices, InputMap},
+    },
+    run::reset_resource,
+    GameState, PauseState,
+};
+
+use super::updating::{character_icon, convert_name};
+use super::DialogueViewSystemSet;
+
+const BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.9);
+const ICON_SIZE: f32 = 64.0;
+const TEXT_WIDTH: f32 = 700.0;
+/// Pixels per second when scrolling with keys or buttons.
+const SCROLL_SPEED: f32 = 400.0;
+/// Pixels per mouse wheel notch.
+const WHEEL_STEP: f32 = 40.0;
+
+#[derive(Clone, Debug)]
+pub struct DialogueLogEntry {
+    /// Already converted, unknown speakers stay unknown in the log too.
+    pub speaker: String,
+    pub text: String,
+    pub icon: Handle<Image>,
+}
+
+#[derive(Resource, Default)]
+pub struct DialogueLog {
+    entries: Vec<DialogueLogEntry>,
+}
+
+impl DialogueLog {
+    pub fn entries(&self) -> &[DialogueLogEntry] {
+        &self.entries
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DialogueLogMenu {
+    open: bool,
+}
+
+impl DialogueLogMenu {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+/// Same as with the codex, menus below only take input again once the log is gone.
+pub fn dialogue_log_closed(
+    menu: Res<DialogueLogMenu>,
+    q_screens: Query<(), With<DialogueLogScreen>>,
+) -> bool {
+    !menu.is_open() && q_screens.is_empty()
+}
+
+#[derive(Component)]
+pub struct DialogueLogScreen;
+#[derive(Component)]
+struct DialogueLogViewport;
+/// How far the log is scrolled up from the newest line.
+#[derive(Component, Default)]
+struct DialogueLogScroll(f32);
+
+fn record_lines(
+    characters: Res<Characters>,
+    locale: Res<Locale>,
+    mut log: ResMut<DialogueLog>,
+    mut ev_present_line: EventReader<PresentLineEvent>,
+) {
+    for ev in ev_present_line.read() {
+        log.entries.push(DialogueLogEntry {
+            speaker: convert_name(
+                ev.line.character_name().unwrap_or_default(),
+                &characters,
+                &locale,
+            ),
+            text: ev.line.text_without_character_name(),
+            icon: character_icon(&characters, &ev.line),
+        });
+    }
+}
+
+fn open_during_dialogue(
+    player_input: Res<PlayerInput>,
+    mut menu: ResMut<DialogueLogMenu>,
+    q_dialogue_runners: Query<&DialogueRunner>,
+) {
+    if player_input.open_dialogue_log && q_dialogue_runners.iter().any(|r| r.is_running()) {
+        menu.open();
+    }
+}
+
+fn text_style(locale: &Res<Locale>, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: locale.font(),
+        font_size,
+        color,
+    }
+}
+
+fn spawn_entry(commands: &mut Commands, locale: &Res<Locale>, entry: &DialogueLogEntry) -> Entity {
+    let icon = commands
+        .spawn(ImageBundle {
+            image: UiImage {
+                texture: entry.icon.clone(),
+                ..default()
+            },
+            style: Style {
+                width: Val::Px(ICON_SIZE),
+                height: Val::Px(ICON_SIZE),
+                flex_shrink: 0.0,
+                ..default()
+            },
+            ..default()
+        })
+        .id();
+
+    let speaker = commands
+        .spawn(TextBundle::from_section(
+            entry.speaker.clone(),
+            text_style(locale, 36.0, GRAY.into()),
+        ))
+        .id();
+    let text = commands
+        .spawn(
+            TextBundle::from_section(entry.text.clone(), text_style(locale, 40.0, Color::WHITE))
+                .with_style(Style {
+                    max_width: Val::Px(TEXT_WIDTH),
+                    ..default()
+                }),
+        )
+        .id();
+    let lines = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(TEXT_WIDTH),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&[speaker, text])
+        .id();
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexStart,
+                column_gap: Val::Px(20.0),
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&[icon, lines])
+        .id()
+}
+
+fn spawn_menu(mut commands: Commands, locale: Res<Locale>, log: Res<DialogueLog>) {
+    let mut entries = Vec::new();
+    for entry in log.entries() {
+        entries.push(spawn_entry(&mut commands, &locale, entry));
+    }
+    if entries.is_empty() {
+        entries.push(
+            commands
+                .spawn((
+                    TextBundle::from_section(
+                        locale.text("dialogue_log.empty"),
+                        text_style(&locale, 40.0, GRAY.into()),
+                    ),
+                    LocalizedText("dialogue_log.empty"),
+                ))
+                .id(),
+        );
+    }
+
+    let scroll = commands
+        .spawn((
+            DialogueLogScroll::default(),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .push_children(&entries)
+        .id();
+
+    let viewport = commands
+        .spawn((
+            DialogueLogViewport,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    flex_grow: 1.0,
+                    flex_direction: FlexDirection::Column,
+                    overflow: Overflow::clip_y(),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .add_child(scroll)
+        .id();
+
+    let header = commands
+        .spawn((
+            TextBundle::from_section(
+                locale.text("menu.dialogue_log"),
+                text_style(&locale, 80.0, Color::WHITE),
+            ),
+            LocalizedText("menu.dialogue_log"),
+        ))
+        .id();
+    let footer = commands
+        .spawn((
+            TextBundle::from_section(
+                locale.text("dialogue_log.footer"),
+                text_style(&locale, 30.0, GRAY.into()),
+            ),
+            LocalizedText("dialogue_log.footer"),
+        ))
+        .id();
+
+    commands
+        .spawn((
+            DialogueLogScreen,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::vertical(Val::Px(20.0)),
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(450),
+                ..default()
+            },
+        ))
+        .push_children(&[header, viewport, footer]);
+}
+
+fn despawn_menu(mut commands: Commands, q_screens: Query<Entity, With<DialogueLogScreen>>) {
+    for entity in &q_screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn close_menu(input_map: Res<InputMap>, devices: InputDevices, mut menu: ResMut<DialogueLogMenu>) {
+    if input_map.just_pressed(InputAction::Escape, &devices)
+        || input_map.just_pressed(InputAction::DialogueLog, &devices)
+    {
+        menu.open = false;
+    }
+}
+
+/// The log also opens from the pause menu, so this has to run on real time.
+fn scroll_log(
+    time: Res<Time<Real>>,
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    q_viewport: Query<&Node, With<DialogueLogViewport>>,
+    mut q_scroll: Query<(&Node, &mut Style, &mut DialogueLogScroll)>,
+    mut ev_mouse_wheel: EventReader<MouseWheel>,
+) {
+    let viewport = match q_viewport.get_single() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+    let (node, mut style, mut scroll) = match q_scroll.get_single_mut() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    let mut direction = devices.left_stick().y;
+    if input_map.pressed(InputAction::DialogueUp, &devices) {
+        direction += 1.0;
+    }
+    if input_map.pressed(InputAction::DialogueDown, &devices) {
+        direction -= 1.0;
+    }
+    let wheel = ev_mouse_wheel.read().map(|ev| ev.y.signum()).sum::<f32>();
+
+    let max_scroll = (node.size().y - viewport.size().y).max(0.0);
+    scroll.0 +=
+        direction.clamp(-1.0, 1.0) * SCROLL_SPEED * time.delta_seconds() + wheel * WHEEL_STEP;
+    scroll.0 = scroll.0.clamp(0.0, max_scroll);
+    style.top = Val::Px(scroll.0 - max_scroll);
+}
+
+/// The menu only reacts to input once it's on screen,
+/// so that the press that opened it doesn't also close it.
+fn menu_shown(menu: Res<DialogueLogMenu>, q_screens: Query<(), With<DialogueLogScreen>>) -> bool {
+    menu.is_open() && !q_screens.is_empty()
+}
+
+fn menu_opened(menu: Res<DialogueLogMenu>, q_screens: Query<(), With<DialogueLogScreen>>) -> bool {
+    menu.is_open() && q_screens.is_empty()
+}
+
+fn menu_closed(menu: Res<DialogueLogMenu>, q_screens: Query<(), With<DialogueLogScreen>>) -> bool {
+    !menu.is_open() && !q_screens.is_empty()
+}
+
+pub struct DialogueLogPlugin;
+
+impl Plugin for DialogueLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DialogueLog>()
+            .init_resource::<DialogueLogMenu>()
+            .add_systems(
+                Update,
+                record_lines
+                    .run_if(on_event::<PresentLineEvent>())
+                    .after(YarnSpinnerSystemSet)
+                    .in_set(DialogueViewSystemSet),
+            )
+            .add_systems(PreUpdate, close_menu.after(InputSystem).run_if(menu_shown))
+            .add_systems(
+                Update,
+                (
+                    open_during_dialogue.run_if(in_state(PauseState::Running)),
+                    spawn_menu.run_if(menu_opened),
+                    despawn_menu.run_if(menu_closed),
+                    scroll_log.run_if(menu_shown),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::AssetLoading))),
+            )
+            .add_systems(OnEnter(GameState::Intro), reset_resource::<DialogueLog>);
+    }
+}
@@ -0,0 +1,231 @@
+//! Yarn markup that changes how a line gets written out.
+//!
+//! - `[pause=0.5/]` waits for the given seconds before continuing
+//! - `[speed=8]...[/speed]` writes with the given graphemes per second
+//! - `[color=red]...[/color]` colors the text, either by name or as hex
+//! - `[wave]...[/wave]` and `[shake]...[/shake]` move the letters around
+//!
+//! Pauses and speeds are meant for a speaker of average speed, they scale with the speaker.
+
+use bevy::{color::palettes::css, prelude::*, text::TextLayoutInfo, ui::widget::text_system};
+use bevy_yarnspinner::prelude::*;
+use noisy_bevy::simplex_noise_2d_seeded;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::GameState;
+
+/// All markup the typewriter knows, everything else is left to yarn.
+pub const MARKUP: [&str; 5] = ["pause", "speed", "color", "wave", "shake"];
+
+const WAVE_AMPLITUDE: f32 = 3.0;
+const WAVE_FREQUENCY: f32 = 6.0;
+/// Phase offset between neighbouring letters.
+const WAVE_PHASE: f32 = 0.6;
+const SHAKE_AMPLITUDE: f32 = 1.5;
+const SHAKE_FREQUENCY: f32 = 25.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextEffect {
+    Wave,
+    Shake,
+}
+
+/// A single grapheme of the line and how it should be written.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct MarkedGrapheme {
+    pub text: String,
+    /// Seconds to wait before this grapheme gets written.
+    pub pause: f32,
+    /// Graphemes per second, the speaker's speed if there is none.
+    pub speed: Option<f32>,
+    pub color: Option<Color>,
+    pub effect: Option<TextEffect>,
+}
+
+enum Markup {
+    Pause(f32),
+    Speed(f32),
+    Color(Color),
+    Effect(TextEffect),
+}
+
+fn markup_number(value: &MarkupValue) -> Option<f32> {
+    match value {
+        MarkupValue::Integer(r) => Some(*r as f32),
+        MarkupValue::Float(r) => Some(*r),
+        _ => None,
+    }
+}
+
+/// Named colors or hex codes, e.g. `red` or `#ff0000`.
+pub fn parse_color(value: &str) -> Option<Color> {
+    let color = match value.to_lowercase().as_str() {
+        "red" => css::RED,
+        "orange" => css::ORANGE,
+        "yellow" => css::YELLOW,
+        "green" => css::LIME,
+        "blue" => css::DODGER_BLUE,
+        "purple" => css::MEDIUM_PURPLE,
+        "pink" => css::HOT_PINK,
+        "gray" | "grey" => css::GRAY,
+        "white" => css::WHITE,
+        "black" => css::BLACK,
+        hex => Srgba::hex(hex).ok()?,
+    };
+    Some(color.into())
+}
+
+impl Markup {
+    fn from_attribute(attribute: &MarkupAttribute) -> Option<Self> {
+        let value = attribute.properties.get(&attribute.name);
+        let markup = match attribute.name.as_str() {
+            "pause" => value.and_then(markup_number).map(Markup::Pause),
+            "speed" => value
+                .and_then(markup_number)
+                .filter(|speed| *speed > 0.0)
+                .map(Markup::Speed),
+            "color" => match value {
+                Some(MarkupValue::String(r)) => parse_color(r).map(Markup::Color),
+                _ => None,
+            },
+            "wave" => Some(Markup::Effect(TextEffect::Wave)),
+            "shake" => Some(Markup::Effect(TextEffect::Shake)),
+            _ => return None,
+        };
+        if markup.is_none() {
+            error!("invalid value in '{}' markup, {:?}", attribute.name, value);
+        }
+        markup
+    }
+}
+
+/// The graphemes of the line without the character name, with all markup applied to them.
+pub fn marked_graphemes(line: &LocalizedLine) -> Vec<MarkedGrapheme> {
+    let text = line.text_without_character_name();
+    // Attribute positions count the chars of the whole line, including the character name.
+    let offset = line
+        .text
+        .chars()
+        .count()
+        .saturating_sub(text.chars().count());
+
+    let mut char_index = 0;
+    let mut graphemes = Vec::new();
+    for grapheme in text.graphemes(true) {
+        graphemes.push((
+            char_index,
+            MarkedGrapheme {
+                text: grapheme.to_string(),
+                ..default()
+            },
+        ));
+        char_index += grapheme.chars().count();
+    }
+
+    for attribute in &line.attributes {
+        let markup = match Markup::from_attribute(attribute) {
+            Some(r) => r,
+            None => continue,
+        };
+        let start = attribute.position.saturating_sub(offset);
+        let end = start + attribute.length;
+
+        if let Markup::Pause(seconds) = markup {
+            // Pauses at the very end of a line have nothing to hold back.
+            if let Some((_, grapheme)) = graphemes.iter_mut().find(|(index, _)| *index >= start) {
+                grapheme.pause += seconds;
+            }
+            continue;
+        }
+
+        for (_, grapheme) in graphemes
+            .iter_mut()
+            .filter(|(index, _)| (start..end).contains(index))
+        {
+            match markup {
+                Markup::Pause(_) => {}
+                Markup::Speed(speed) => grapheme.speed = Some(speed),
+                Markup::Color(color) => grapheme.color = Some(color),
+                Markup::Effect(effect) => grapheme.effect = Some(effect),
+            }
+        }
+    }
+    graphemes
+        .into_iter()
+        .map(|(_, grapheme)| grapheme)
+        .collect()
+}
+
+/// The effect of every section of the text, glyphs get moved according to these after layout.
+#[derive(Component, Default)]
+pub struct TextEffects {
+    sections: Vec<Option<TextEffect>>,
+    /// Glyph positions as the layout put them, so that offsets don't add up.
+    base_positions: Vec<Vec2>,
+}
+
+impl TextEffects {
+    pub fn new(sections: Vec<Option<TextEffect>>) -> Self {
+        Self {
+            sections,
+            base_positions: Vec::new(),
+        }
+    }
+}
+
+fn effect_offset(effect: TextEffect, elapsed: f32, index: usize) -> Vec2 {
+    match effect {
+        TextEffect::Wave => Vec2::new(
+            0.0,
+            (elapsed * WAVE_FREQUENCY + index as f32 * WAVE_PHASE).sin() * WAVE_AMPLITUDE,
+        ),
+        TextEffect::Shake => {
+            let noise = |seed: f32| {
+                simplex_noise_2d_seeded(Vec2::new(elapsed * SHAKE_FREQUENCY, 0.0), seed)
+            };
+            Vec2::new(noise(index as f32 * 2.0), noise(index as f32 * 2.0 + 1.0)) * SHAKE_AMPLITUDE
+        }
+    }
+}
+
+fn animate_text_effects(
+    time: Res<Time>,
+    mut q_texts: Query<(&mut TextLayoutInfo, &mut TextEffects)>,
+) {
+    for (mut layout, mut effects) in &mut q_texts {
+        if layout.is_changed() {
+            effects.base_positions = layout.glyphs.iter().map(|glyph| glyph.position).collect();
+        }
+        if effects.sections.iter().all(Option::is_none) {
+            continue;
+        }
+
+        // Only the text system should trigger a new layout, not the offsets we add on top.
+        let layout = layout.bypass_change_detection();
+        for (index, (glyph, base)) in layout
+            .glyphs
+            .iter_mut()
+            .zip(&effects.base_positions)
+            .enumerate()
+        {
+            let offset = match effects.sections.get(glyph.section_index) {
+                Some(Some(effect)) => effect_offset(*effect, time.elapsed_seconds(), index),
+                _ => Vec2::ZERO,
+            };
+            glyph.position = *base + offset;
+        }
+    }
+}
+
+pub struct DialogueMarkupPlugin;
+
+impl Plugin for DialogueMarkupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            animate_text_effects
+                .after(text_system)
+                .run_if(not(in_state(GameState::AssetLoading))),
+        );
+    }
+}
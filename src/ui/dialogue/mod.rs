@@ -1,7 +1,12 @@
+pub mod advance;
+pub mod log;
 pub mod runner;
+pub mod variables;
 
 mod audio;
 mod command;
+mod markup;
+mod option_selection;
 mod spawn;
 #[cfg(test)]
 mod test;
@@ -21,6 +26,11 @@ impl Plugin for DialoguePlugin {
             typewriter::DialogueTypewriterPlugin,
             runner::DialogueRunnerPlugin,
             audio::DialogueAudioPlugin,
+            option_selection::DialogueOptionSelectionPlugin,
+            variables::DialogueVariablesPlugin,
+            log::DialogueLogPlugin,
+            advance::DialogueAdvancePlugin,
+            markup::DialogueMarkupPlugin,
         ));
     }
 }
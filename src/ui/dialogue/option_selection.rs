@@ -0,0 +1,213 @@
+use bevy::{
+    color::palettes::css::{DARK_GRAY, GRAY},
+    prelude::*,
+};
+use bevy_yarnspinner::{events::*, prelude::*};
+
+use crate::player::input::PlayerInput;
+use crate::run::reset_resource;
+use crate::{locale::Locale, GameState};
+
+use super::spawn::{DialogueContinueNode, DialogueOptionsNode};
+use super::updating::continue_dialogue;
+use super::DialogueViewSystemSet;
+
+#[derive(Component)]
+struct OptionButton {
+    index: usize,
+}
+
+#[derive(Resource, Default)]
+struct OptionSelection {
+    source: Option<Entity>,
+    options: Vec<DialogueOption>,
+    selected: usize,
+}
+
+impl OptionSelection {
+    fn is_available(&self, index: usize) -> bool {
+        self.options.get(index).is_some_and(|o| o.is_available)
+    }
+
+    /// Move the selection by `direction`, skipping unavailable options and wrapping around.
+    fn step(&mut self, direction: i8) {
+        let len = self.options.len();
+        if len == 0 {
+            return;
+        }
+
+        let mut index = self.selected;
+        for _ in 0..len {
+            index = if direction > 0 {
+                (index + len - 1) % len
+            } else {
+                (index + 1) % len
+            };
+            if self.is_available(index) {
+                self.selected = index;
+                return;
+            }
+        }
+    }
+}
+
+fn text_style_option(locale: &Locale) -> TextStyle {
+    TextStyle {
+        font: locale.font(),
+        font_size: 46.0,
+        color: GRAY.into(),
+    }
+}
+
+fn present_options(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    mut selection: ResMut<OptionSelection>,
+    q_options_node: Query<Entity, With<DialogueOptionsNode>>,
+    mut q_continue_visibility: Query<&mut Visibility, With<DialogueContinueNode>>,
+    mut ev_present_options: EventReader<PresentOptionsEvent>,
+) {
+    let options_node = match q_options_node.get_single() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    for ev in ev_present_options.read() {
+        commands.entity(options_node).despawn_descendants();
+
+        *selection = OptionSelection {
+            source: Some(ev.source),
+            options: ev.options.clone(),
+            selected: 0,
+        };
+        if !selection.is_available(0) {
+            selection.step(-1);
+        }
+
+        for (index, option) in ev.options.iter().enumerate() {
+            let text = commands
+                .spawn((
+                    TextBundle::from_section(
+                        option.line.text_without_character_name(),
+                        text_style_option(&locale),
+                    ),
+                    Label,
+                ))
+                .id();
+            let button = commands
+                .spawn((OptionButton { index }, ButtonBundle::default()))
+                .add_child(text)
+                .id();
+            commands.entity(options_node).add_child(button);
+        }
+    }
+
+    if let Ok(mut visibility) = q_continue_visibility.get_single_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn navigate_options(
+    input: Res<PlayerInput>,
+    mut selection: ResMut<OptionSelection>,
+    q_buttons: Query<(&Interaction, &OptionButton), Changed<Interaction>>,
+) {
+    if selection.source.is_none() {
+        return;
+    }
+
+    for (interaction, button) in &q_buttons {
+        if *interaction != Interaction::None && selection.is_available(button.index) {
+            selection.selected = button.index;
+        }
+    }
+
+    if input.dialogue_direction != 0 {
+        selection.step(input.dialogue_direction);
+    }
+}
+
+fn select_option(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    mut selection: ResMut<OptionSelection>,
+    q_buttons: Query<(&Interaction, &OptionButton), Changed<Interaction>>,
+    q_options_node: Query<Entity, With<DialogueOptionsNode>>,
+    mut q_dialogue_runners: Query<&mut DialogueRunner>,
+) {
+    let source = match selection.source {
+        Some(r) => r,
+        None => return,
+    };
+
+    let clicked = q_buttons.iter().any(|(interaction, button)| {
+        *interaction == Interaction::Pressed && selection.is_available(button.index)
+    });
+    if !input.dialogue_confirm && !clicked {
+        return;
+    }
+    if !selection.is_available(selection.selected) {
+        return;
+    }
+
+    let option_id = selection.options[selection.selected].id;
+    match q_dialogue_runners.get_mut(source) {
+        Ok(mut dialogue_runner) => {
+            if let Err(err) = dialogue_runner.select_option(option_id) {
+                error!("failed to select dialogue option, {}", err);
+                return;
+            }
+        }
+        Err(_) => warn!("the dialogue runner presenting the options no longer exists"),
+    }
+
+    *selection = OptionSelection::default();
+    for entity in &q_options_node {
+        commands.entity(entity).despawn_descendants();
+    }
+}
+
+fn highlight_options(
+    selection: Res<OptionSelection>,
+    q_buttons: Query<(&OptionButton, &Children)>,
+    mut q_texts: Query<&mut Text>,
+) {
+    for (button, children) in &q_buttons {
+        let color = if !selection.is_available(button.index) {
+            DARK_GRAY.into()
+        } else if button.index == selection.selected {
+            Color::WHITE
+        } else {
+            GRAY.into()
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = q_texts.get_mut(*child) {
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+}
+
+pub struct DialogueOptionSelectionPlugin;
+
+impl Plugin for DialogueOptionSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                present_options.run_if(on_event::<PresentOptionsEvent>()),
+                navigate_options,
+                select_option,
+                highlight_options,
+            )
+                .chain()
+                .after(YarnSpinnerSystemSet)
+                .after(continue_dialogue)
+                .in_set(DialogueViewSystemSet)
+                .run_if(not(in_state(GameState::AssetLoading))),
+        )
+        .init_resource::<OptionSelection>()
+        .add_systems(OnEnter(GameState::Intro), reset_resource::<OptionSelection>);
+    }
+}
@@ -5,15 +5,14 @@ use bevy_yarnspinner::{events::DialogueCompleteEvent, prelude::*};
 
 use crate::{
     aspect::{CombinedAspect, Combiner},
+    locale::Locale,
     npc::narrator::TriggeredNarratorDialogue,
+    run::{reset_resource, RunScoped},
     world::{PlayerWentToBed, TriggerFirstImaDialogue},
     GameState,
 };
 
-use super::{
-    command::{trigger_ending_command, trigger_game_over_command},
-    spawn::DialogueRoot,
-};
+use super::{command::register_commands, spawn::DialogueRoot};
 
 const SHORT_INTRO_TIMEOUT: f32 = 3.5;
 pub const IMA_FINAL_DIALOGUE: &str = "ImaFinalDialogue";
@@ -21,7 +20,7 @@ pub const IMA_FIRST_ENCOUNTER: &str = "ImaFirstEncounter";
 pub const IMA_FIRST_ENCOUNTER_SHORT: &str = "ImaFirstEncounterShort";
 
 #[derive(Resource)]
-struct TimeSinceGaming(Timer);
+pub struct TimeSinceGaming(pub Timer);
 
 impl Default for TimeSinceGaming {
     fn default() -> Self {
@@ -37,24 +36,35 @@ pub struct RunnerFlags {
     pub line: Option<LocalizedLine>,
 }
 
-fn spawn_runner(commands: &mut Commands, project: &Res<YarnProject>, node: &str) {
-    let mut dialogue_runner = project.create_dialogue_runner();
-    dialogue_runner
-        .commands_mut()
-        .add_command("trigger_ending", trigger_ending_command)
-        .add_command("game_over", trigger_game_over_command);
+fn spawn_runner(
+    commands: &mut Commands,
+    project: &Res<YarnProject>,
+    variables: &DialogueVariables,
+    locale: &Locale,
+    node: &str,
+) {
+    let mut dialogue_runner = project
+        .build_dialogue_runner()
+        .with_variable_storage(variables.storage())
+        .build();
+    register_commands(dialogue_runner.commands_mut());
+    dialogue_runner.set_language(locale.language().code());
     dialogue_runner.start_node(node);
-    commands.spawn((dialogue_runner, RunnerFlags::default()));
+    commands.spawn((RunScoped, dialogue_runner, RunnerFlags::default()));
 }
 
 fn spawn_dialogue_runner(
     mut commands: Commands,
     project: Res<YarnProject>,
+    variables: Res<DialogueVariables>,
+    locale: Res<Locale>,
     combiner: Res<Combiner>,
 ) {
     spawn_runner(
         &mut commands,
         &project,
+        &variables,
+        &locale,
         &combiner.last_combined_aspect.to_string(),
     );
 }
@@ -62,10 +72,12 @@ fn spawn_dialogue_runner(
 fn spawn_narrator_dialogue(
     mut commands: Commands,
     project: Res<YarnProject>,
+    variables: Res<DialogueVariables>,
+    locale: Res<Locale>,
     mut ev_triggered_narrator_dialogue: EventReader<TriggeredNarratorDialogue>,
 ) {
     for ev in ev_triggered_narrator_dialogue.read() {
-        spawn_runner(&mut commands, &project, &ev.0.to_string());
+        spawn_runner(&mut commands, &project, &variables, &locale, ev.0.node());
     }
 }
 
@@ -73,17 +85,30 @@ fn spawn_ima_first_encounter(
     mut commands: Commands,
     time_since_gaming: Res<TimeSinceGaming>,
     project: Res<YarnProject>,
+    variables: Res<DialogueVariables>,
+    locale: Res<Locale>,
 ) {
     let node = if time_since_gaming.0.finished() {
         IMA_FIRST_ENCOUNTER
     } else {
         IMA_FIRST_ENCOUNTER_SHORT
     };
-    spawn_runner(&mut commands, &project, node);
+    spawn_runner(&mut commands, &project, &variables, &locale, node);
 }
 
-fn spawn_ima_final_dialogue(mut commands: Commands, project: Res<YarnProject>) {
-    spawn_runner(&mut commands, &project, IMA_FINAL_DIALOGUE);
+fn spawn_ima_final_dialogue(
+    mut commands: Commands,
+    project: Res<YarnProject>,
+    variables: Res<DialogueVariables>,
+    locale: Res<Locale>,
+) {
+    spawn_runner(
+        &mut commands,
+        &project,
+        &variables,
+        &locale,
+        IMA_FINAL_DIALOGUE,
+    );
 }
 
 fn despawn_dialogue(
@@ -124,6 +149,7 @@ impl Plugin for DialogueRunnerPlugin {
             Update,
             tick_time_since_gaming.run_if(in_state(GameState::Gaming)),
         )
-        .init_resource::<TimeSinceGaming>();
+        .init_resource::<TimeSinceGaming>()
+        .add_systems(OnEnter(GameState::Intro), reset_resource::<TimeSinceGaming>);
     }
 }
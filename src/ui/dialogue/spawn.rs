@@ -3,11 +3,15 @@ use bevy_tweening::{lens::*, *};
 
 use crate::{
     aspect::CombinedAspect,
+    locale::Locale,
     npc::narrator::TriggeredNarratorDialogue,
+    run::RunScoped,
     world::{PlayerWentToBed, TriggerFirstImaDialogue},
     GameAssets, GameState,
 };
 
+use super::markup::{MarkedGrapheme, TextEffects};
+
 // The master root of the dialogue
 #[derive(Component)]
 pub struct DialogueRoot;
@@ -19,6 +23,8 @@ pub struct DialogueNameNode;
 pub struct DialogueCharacterIcon;
 #[derive(Component)]
 pub struct DialogueContinueNode;
+#[derive(Component)]
+pub struct DialogueOptionsNode;
 
 const DIALOG_WIDTH: f32 = 800.0 * 0.8;
 const TEXT_BORDER: f32 = 120.0;
@@ -34,23 +40,27 @@ fn style_standard(_assets: &Res<GameAssets>) -> Style {
     }
 }
 
-fn text_style_standard(assets: &Res<GameAssets>) -> TextStyle {
+fn text_style_standard(locale: &Locale) -> TextStyle {
     TextStyle {
-        font: assets.silver_font.clone(),
+        font: locale.font(),
         font_size: 50.0,
         color: Color::WHITE,
     }
 }
 
-fn text_style_name(assets: &Res<GameAssets>) -> TextStyle {
+fn text_style_name(locale: &Locale) -> TextStyle {
     TextStyle {
-        font: assets.silver_font.clone(),
+        font: locale.font(),
         font_size: 46.0,
         color: Color::WHITE,
     }
 }
 
-fn spawn_dialogue_top(commands: &mut Commands, assets: &Res<GameAssets>) -> Entity {
+fn spawn_dialogue_top(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    locale: &Locale,
+) -> Entity {
     let edge = commands
         .spawn((ImageBundle {
             image: UiImage {
@@ -68,7 +78,7 @@ fn spawn_dialogue_top(commands: &mut Commands, assets: &Res<GameAssets>) -> Enti
     let name_node = commands
         .spawn((
             TextBundle {
-                text: Text::from_section(String::new(), text_style_name(assets)),
+                text: Text::from_section(String::new(), text_style_name(locale)),
                 style: Style {
                     position_type: PositionType::Absolute,
                     left: Val::Px(TEXT_BORDER / 2.0),
@@ -97,16 +107,35 @@ fn spawn_dialogue_top(commands: &mut Commands, assets: &Res<GameAssets>) -> Enti
         .id()
 }
 
-fn spawn_dialogue_content(commands: &mut Commands, assets: &Res<GameAssets>) -> Entity {
+fn spawn_dialogue_content(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    locale: &Locale,
+) -> Entity {
     let text = commands
         .spawn((
             DialogueContent,
+            TextEffects::default(),
             Label,
-            TextBundle::from_section(String::new(), text_style_standard(assets))
+            TextBundle::from_section(String::new(), text_style_standard(locale))
                 .with_style(style_standard(assets)),
         ))
         .id();
 
+    let options = commands
+        .spawn((
+            DialogueOptionsNode,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .id();
+
     let icon = commands
         .spawn((
             DialogueCharacterIcon,
@@ -136,7 +165,7 @@ fn spawn_dialogue_content(commands: &mut Commands, assets: &Res<GameAssets>) ->
             background_color: Color::BLACK.with_alpha(0.8).into(),
             ..default()
         },))
-        .push_children(&[text, icon])
+        .push_children(&[text, options, icon])
         .id()
 }
 
@@ -214,10 +243,11 @@ fn spawn_dialogue_bottom(commands: &mut Commands, assets: &Res<GameAssets>) -> E
 fn spawn_dialogue(
     commands: &mut Commands,
     assets: &Res<GameAssets>,
+    locale: &Locale,
     justify_content: JustifyContent,
 ) {
-    let dialogue_top = spawn_dialogue_top(commands, assets);
-    let dialogue_content = spawn_dialogue_content(commands, assets);
+    let dialogue_top = spawn_dialogue_top(commands, assets, locale);
+    let dialogue_content = spawn_dialogue_content(commands, assets, locale);
     let dialogue_bottom = spawn_dialogue_bottom(commands, assets);
 
     let dialogue_root = commands
@@ -238,6 +268,7 @@ fn spawn_dialogue(
 
     commands
         .spawn((
+            RunScoped,
             DialogueRoot,
             NodeBundle {
                 style: Style {
@@ -253,32 +284,49 @@ fn spawn_dialogue(
         .push_children(&[dialogue_root]);
 }
 
-fn spawn_npc_dialogue(mut commands: Commands, assets: Res<GameAssets>) {
-    spawn_dialogue(&mut commands, &assets, JustifyContent::FlexEnd);
+fn spawn_npc_dialogue(mut commands: Commands, assets: Res<GameAssets>, locale: Res<Locale>) {
+    spawn_dialogue(&mut commands, &assets, &locale, JustifyContent::FlexEnd);
 }
 
-fn spawn_narrator_dialogue(mut commands: Commands, assets: Res<GameAssets>) {
-    spawn_dialogue(&mut commands, &assets, JustifyContent::Center);
+fn spawn_narrator_dialogue(mut commands: Commands, assets: Res<GameAssets>, locale: Res<Locale>) {
+    spawn_dialogue(&mut commands, &assets, &locale, JustifyContent::Center);
 }
 
+/// The written graphemes in the style of their markup.
+/// The rest is invisible, so that words don't jump to the next line while they are written.
 pub fn create_dialogue_text(
-    text: impl Into<String>,
-    invisible: impl Into<String>,
-    assets: &Res<GameAssets>,
-) -> Text {
-    Text::from_sections([
-        TextSection {
-            value: text.into(),
-            style: text_style_standard(assets),
-        },
-        TextSection {
-            value: invisible.into(),
-            style: TextStyle {
-                color: Color::NONE,
-                ..text_style_standard(assets)
-            },
-        },
-    ])
+    graphemes: &[MarkedGrapheme],
+    written: usize,
+    locale: &Locale,
+) -> (Text, TextEffects) {
+    let mut sections: Vec<TextSection> = Vec::new();
+    let mut effects = Vec::new();
+    for (index, grapheme) in graphemes.iter().enumerate() {
+        let color = if index < written {
+            grapheme.color.unwrap_or(Color::WHITE)
+        } else {
+            Color::NONE
+        };
+
+        match sections.last_mut() {
+            Some(section)
+                if section.style.color == color && effects.last() == Some(&grapheme.effect) =>
+            {
+                section.value += &grapheme.text;
+            }
+            _ => {
+                sections.push(TextSection {
+                    value: grapheme.text.clone(),
+                    style: TextStyle {
+                        color,
+                        ..text_style_standard(locale)
+                    },
+                });
+                effects.push(grapheme.effect);
+            }
+        }
+    }
+    (Text::from_sections(sections), TextEffects::new(effects))
 }
 
 pub struct DialogueSpawnPlugin;
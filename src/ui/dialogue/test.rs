@@ -5,19 +5,34 @@ use std::{
     str::FromStr,
 };
 
+use bevy::prelude::*;
+use bevy_trickfilm::prelude::*;
+use bevy_yarnspinner::{events::PresentLineEvent, prelude::*};
 use strum::IntoEnumIterator;
 
 use crate::{
-    aspect::Aspect,
-    npc::{
-        narrator::{evaluate_aspect, NarratorDialogue},
-        NpcDialogue,
+    aspect::{Aspect, Combiner},
+    audio::BgmTrack,
+    harness::{shipped_characters, shipped_rules},
+    npc::narrator::{evaluate_aspect, NarratorDialogue},
+    ui::dialogue::{
+        command::{YarnSound, YARN_COMMANDS},
+        markup::{marked_graphemes, parse_color, TextEffect, MARKUP},
+        runner::{IMA_FINAL_DIALOGUE, IMA_FIRST_ENCOUNTER, IMA_FIRST_ENCOUNTER_SHORT},
+        spawn::DialogueCharacterIcon,
+        updating::update_dialogue_character_icon,
+        variables::{
+            aspect_present_variable, game_variables, DialogueVariables, BALANCE_SCORE,
+            COMBINATIONS_MADE, LAST_COMBINED_ASPECT,
+        },
     },
-    ui::dialogue::runner::{IMA_FINAL_DIALOGUE, IMA_FIRST_ENCOUNTER, IMA_FIRST_ENCOUNTER_SHORT},
 };
 
 const PATH_TO_DIR: &str = "assets/dialogue";
-const VALID_COMMANDS: [&str; 2] = ["<<trigger_ending>>", "<<game_over>>"];
+/// Statements that are part of the yarn language itself, not commands.
+const YARN_KEYWORDS: [&str; 8] = [
+    "jump", "set", "declare", "if", "elseif", "else", "endif", "stop",
+];
 
 fn try_read_yarn_contents(entry: Result<DirEntry, Error>) -> Option<(String, String)> {
     let entry = entry.expect("Can't get entry in current dir");
@@ -78,14 +93,22 @@ fn validate_node_title_uniqueness() {
 
 #[test]
 fn validate_npc_names() {
+    let characters = shipped_characters();
+
     validate_lines(|line, _| {
+        // Options may have a character name too, e.g. `-> Ami: I'm fine.`
+        let line = line.strip_prefix("-> ").unwrap_or(line);
         if let Some((possible_name, possible_message)) = line.split_once(' ') {
             if let Some(name) = possible_name.strip_suffix(':') {
                 let name = name.trim_start_matches('_');
                 if name == "title" {
                     return;
                 }
-                NpcDialogue::from_str(name).expect(&format!("Not a valid npc name, {}", name));
+                assert!(
+                    characters.get(name).is_some(),
+                    "Not a valid npc name, {}",
+                    name
+                );
             } else {
                 if possible_message.contains(':') {
                     panic!("The line, '{line}', contains a ':' in the message but has no character title. This is really bad.");
@@ -95,19 +118,257 @@ fn validate_npc_names() {
     });
 }
 
+/// Tags other than the line id pick the expression of the speaker's portrait.
+#[test]
+fn validate_expression_tags() {
+    let characters = shipped_characters();
+
+    validate_lines(|line, npc_file_name| {
+        if line.starts_with("<<") || line.starts_with("title: ") {
+            return;
+        }
+        let line = line.strip_prefix("-> ").unwrap_or(line);
+        let tags = line
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('#'))
+            .filter(|tag| !tag.contains(':'))
+            .collect::<Vec<&str>>();
+        if tags.is_empty() {
+            return;
+        }
+
+        let name = line
+            .split_once(": ")
+            .map(|(name, _)| name.trim_start_matches('_'))
+            .unwrap_or_default();
+        let profile = characters.get(name).unwrap_or_else(|| {
+            panic!(
+                "The narrator has no portrait for the tags in '{line}', in file: '{npc_file_name}'"
+            )
+        });
+        for tag in tags {
+            assert!(
+                profile.expressions.contains_key(tag),
+                "'{}' has no expression for '#{tag}', in file: '{npc_file_name}'",
+                profile.name
+            );
+        }
+    });
+}
+
 #[test]
 fn validate_commands() {
-    validate_lines(|line, _| {
-        if line.starts_with("<<") {
+    validate_lines(|line, npc_file_name| {
+        let command = match line.strip_prefix("<<") {
+            Some(r) => r.trim_end_matches(">>"),
+            None => return,
+        };
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        if YARN_KEYWORDS.contains(&name) {
+            return;
+        }
+        let args = words.collect::<Vec<&str>>();
+
+        let registered = YARN_COMMANDS
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap_or_else(|| {
+                panic!("Command '{name}' in file: '{npc_file_name}' is not in `YARN_COMMANDS`")
+            });
+        assert_eq!(
+            args.len(),
+            registered.args,
+            "Command '{name}' in file: '{npc_file_name}' takes {} arguments, but got '{line}'",
+            registered.args
+        );
+
+        match name {
+            "camera_shake" | "wait" => {
+                args[0]
+                    .parse::<f32>()
+                    .unwrap_or_else(|_| panic!("Not a number in '{line}'"));
+            }
+            "play_sound" => {
+                YarnSound::from_str(args[0]).expect(&format!("Not a valid sound in '{line}'"));
+            }
+            "set_bgm" => {
+                BgmTrack::from_str(args[0]).expect(&format!("Not a valid bgm track in '{line}'"));
+            }
+            _ => {}
+        }
+    });
+}
+
+#[test]
+fn validate_markup() {
+    validate_lines(|line, npc_file_name| {
+        if line.starts_with("<<") || line.starts_with("title: ") {
+            return;
+        }
+        for tag in line.split('[').skip(1).filter_map(|r| r.split_once(']')) {
+            let tag = tag.0.trim_start_matches('/').trim_end_matches('/').trim();
+            // Closing tags have no value, only the opening ones need to be checked.
+            let (name, value) = match tag.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (tag, None),
+            };
             assert!(
-                VALID_COMMANDS.contains(&line),
-                "Only supported commands are {:?}, but got '{line}'",
-                VALID_COMMANDS
+                MARKUP.contains(&name),
+                "Markup '{name}' in file: '{npc_file_name}' is not in `MARKUP`, '{line}'"
             );
+
+            match (name, value) {
+                (_, None) => {}
+                ("pause" | "speed", Some(value)) => {
+                    value
+                        .parse::<f32>()
+                        .unwrap_or_else(|_| panic!("Not a number in '{line}'"));
+                }
+                ("color", Some(value)) => {
+                    parse_color(value).unwrap_or_else(|| panic!("Not a valid color in '{line}'"));
+                }
+                _ => {}
+            }
         }
     });
 }
 
+fn markup_attribute(
+    name: &str,
+    position: usize,
+    length: usize,
+    value: Option<MarkupValue>,
+) -> MarkupAttribute {
+    MarkupAttribute {
+        name: name.to_string(),
+        position,
+        length,
+        properties: value.map(|v| (name.to_string(), v)).into_iter().collect(),
+        source_position: position,
+    }
+}
+
+/// `Ami: text`, with the character attribute yarn adds and `attributes` on top.
+/// Positions of the attributes count the chars of the whole line, including `Ami: `.
+fn ami_line(text: &str, attributes: Vec<MarkupAttribute>) -> LocalizedLine {
+    let mut character = markup_attribute("character", 0, 5, None);
+    character
+        .properties
+        .insert("name".to_string(), MarkupValue::String("Ami".to_string()));
+    LocalizedLine {
+        id: LineId("line:test".to_string()),
+        text: format!("Ami: {}", text),
+        attributes: [vec![character], attributes].concat(),
+        metadata: Vec::new(),
+        assets: LineAssets::new(),
+    }
+}
+
+#[test]
+fn validate_marked_graphemes_without_name() {
+    let line = ami_line("Hi there", Vec::new());
+    let text = marked_graphemes(&line)
+        .into_iter()
+        .map(|grapheme| grapheme.text)
+        .collect::<String>();
+    assert_eq!(text, "Hi there");
+}
+
+#[test]
+fn validate_marked_graphemes_timing() {
+    // `Ami: Hi[pause=0.5/] [speed=8]there[/speed]`
+    let line = ami_line(
+        "Hi there",
+        vec![
+            markup_attribute("pause", 7, 0, Some(MarkupValue::Float(0.5))),
+            markup_attribute("speed", 8, 5, Some(MarkupValue::Integer(8))),
+        ],
+    );
+    let graphemes = marked_graphemes(&line);
+
+    let pauses = graphemes.iter().map(|g| g.pause).collect::<Vec<f32>>();
+    assert_eq!(pauses, vec![0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0]);
+    let speeds = graphemes
+        .iter()
+        .map(|g| g.speed)
+        .collect::<Vec<Option<f32>>>();
+    assert_eq!(speeds[..3], [None, None, None]);
+    assert!(speeds[3..].iter().all(|speed| *speed == Some(8.0)));
+}
+
+#[test]
+fn validate_marked_graphemes_ranges() {
+    // `Ami: [color=red]red[/color] [wave]wave[/wave]`
+    let line = ami_line(
+        "red wave",
+        vec![
+            markup_attribute("color", 5, 3, Some(MarkupValue::String("red".to_string()))),
+            markup_attribute("wave", 9, 4, None),
+        ],
+    );
+    let graphemes = marked_graphemes(&line);
+
+    let red = parse_color("red");
+    let colors = graphemes.iter().map(|g| g.color).collect::<Vec<_>>();
+    assert_eq!(colors[..3], [red, red, red]);
+    assert!(colors[3..].iter().all(Option::is_none));
+
+    let effects = graphemes.iter().map(|g| g.effect).collect::<Vec<_>>();
+    assert!(effects[..4].iter().all(Option::is_none));
+    assert!(effects[4..]
+        .iter()
+        .all(|effect| *effect == Some(TextEffect::Wave)));
+}
+
+#[test]
+fn validate_neutral_portrait_drops_sprite_sheet() {
+    let mut app = App::new();
+    app.add_event::<PresentLineEvent>()
+        .insert_resource(shipped_characters())
+        .add_systems(Update, update_dialogue_character_icon);
+    // As it's left by a line with an animated expression.
+    let icon = app
+        .world_mut()
+        .spawn((
+            DialogueCharacterIcon,
+            UiImage::new(Handle::weak_from_u128(1)),
+            TextureAtlas::default(),
+            AnimationPlayer2D::default(),
+        ))
+        .id();
+
+    // Ami has no expression for this tag, so she goes back to her neutral portrait.
+    let mut line = ami_line("Hi there", Vec::new());
+    line.metadata = vec!["bored".to_string()];
+    app.world_mut().send_event(PresentLineEvent {
+        line,
+        source: Entity::PLACEHOLDER,
+    });
+    app.update();
+
+    let icon = app.world().entity(icon);
+    assert!(icon.get::<TextureAtlas>().is_none());
+    assert!(icon.get::<AnimationPlayer2D>().is_none());
+    let characters = shipped_characters();
+    assert_eq!(
+        icon.get::<UiImage>().unwrap().texture,
+        characters.get("Ami").unwrap().portrait
+    );
+}
+
+#[test]
+fn validate_command_names_are_unique() {
+    let mut names = HashSet::new();
+    for command in &YARN_COMMANDS {
+        assert!(
+            names.insert(command.name),
+            "Command registered twice: {}",
+            command.name
+        );
+    }
+}
+
 #[test]
 fn validate_node_title_aspect_matching() {
     let mut titles = Vec::new();
@@ -128,9 +389,10 @@ fn validate_node_title_aspect_matching() {
 
 #[test]
 fn validate_node_title_narrator_matching() {
-    let mut titles = Vec::new();
-    for title in NarratorDialogue::iter() {
-        titles.push(title.to_string());
+    let rules = shipped_rules();
+    let mut titles = vec![NarratorDialogue::INTRO.to_string()];
+    for rule in rules.rules() {
+        titles.push(rule.node.clone());
     }
     let mut titles_hashset = HashSet::new();
 
@@ -141,7 +403,12 @@ fn validate_node_title_narrator_matching() {
             }
         }
     });
-    assert!(titles.len() == titles_hashset.len(), "Length mismatch, not all endings have their own title in yarn files, total of {} endings exist, but only {} of those have a title in yarn", titles.len(), titles_hashset.len());
+    for title in &titles {
+        assert!(
+            titles_hashset.contains(title),
+            "The narrator node '{title}' from the ending rules has no title in the yarn files"
+        );
+    }
 }
 
 #[test]
@@ -205,3 +472,55 @@ fn validate_ending_evaluation() {
         sum
     );
 }
+
+#[test]
+fn validate_game_variables() {
+    let combiner = Combiner {
+        last_combined_aspect: Aspect::Elation,
+        combined_aspects: vec![Aspect::Nostalgia, Aspect::Hatred, Aspect::Elation],
+        ..Default::default()
+    };
+    let variables = game_variables(&combiner, &[Aspect::Joy, Aspect::Anger, Aspect::Fear]);
+
+    assert_eq!(variables[COMBINATIONS_MADE], YarnValue::Number(3.0));
+    assert_eq!(variables[BALANCE_SCORE], YarnValue::Number(-1.0));
+    assert_eq!(
+        variables[LAST_COMBINED_ASPECT],
+        YarnValue::String("Elation".to_string())
+    );
+    assert_eq!(variables["$aspect_joy_present"], YarnValue::Boolean(true));
+    assert_eq!(
+        variables[&aspect_present_variable(Aspect::Forgiveness)],
+        YarnValue::Boolean(false)
+    );
+}
+
+#[test]
+fn validate_dialogue_variables_are_shared() {
+    let variables = DialogueVariables::default();
+    variables.set_game_variables(game_variables(&Combiner::default(), &[]));
+
+    let mut storage = variables.storage();
+    let other = storage.clone_shallow();
+    storage
+        .set("$met_ima".to_string(), YarnValue::Boolean(true))
+        .expect("Can't set yarn variable");
+    assert_eq!(other.get("$met_ima").ok(), Some(YarnValue::Boolean(true)));
+
+    // Game variables are read only, yarn may not overwrite them.
+    assert!(storage
+        .set(COMBINATIONS_MADE.to_string(), YarnValue::Number(10.0))
+        .is_err());
+    assert!(storage
+        .set("missing_dollar".to_string(), YarnValue::Number(1.0))
+        .is_err());
+
+    let saved = variables.saved_variables();
+    assert_eq!(saved.len(), 1);
+    let loaded = DialogueVariables::default();
+    loaded.load_saved_variables(&saved);
+    assert_eq!(
+        loaded.storage().get("$met_ima").ok(),
+        Some(YarnValue::Boolean(true))
+    );
+}
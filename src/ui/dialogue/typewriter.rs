@@ -1,23 +1,20 @@
-use std::str::FromStr;
-
-use unicode_segmentation::UnicodeSegmentation;
-
 use bevy::prelude::*;
-use bevy::utils::Instant;
 use bevy_yarnspinner::{events::*, prelude::*};
 
-use crate::npc::NpcDialogue;
+use crate::npc::characters::{CharacterProfile, Characters};
+use crate::run::reset_resource;
 use crate::utils::DebugActive;
-use crate::{GameAssets, GameState};
+use crate::{locale::Locale, GameState};
 
 use super::audio::PlayBlipEvent;
+use super::markup::{marked_graphemes, MarkedGrapheme, TextEffects};
 use super::spawn::{create_dialogue_text, DialogueContent, DialogueContinueNode};
 use super::DialogueViewSystemSet;
 
 // Write dialogue instantly, for going through dialogue fast.
 const DEBUG_SPEED: f32 = 1000.0;
 // The average speed over all people.
-// It's used to calculate the multiplier of the pauses caused by punctuation.
+// It's used to calculate the multiplier of the pauses caused by punctuation and markup.
 const AVERAGE_SPEED: f32 = 20.0;
 
 #[derive(Event)]
@@ -26,23 +23,30 @@ pub struct TypewriterFinished;
 #[derive(Resource)]
 pub struct Typewriter {
     pub character_name: Option<String>,
-    pub current_text: String,
-    pub graphemes_left: Vec<String>,
+    graphemes: Vec<MarkedGrapheme>,
+    /// How many of the graphemes are already on screen.
+    written: usize,
     elapsed: f32,
-    start: Instant,
     last_finished: bool,
     current_speed: f32,
+    /// The speaker of the current line.
+    voice: CharacterProfile,
+    /// Write out the rest of the line at once on the next update.
+    completing: bool,
+    skipping_seen_line: bool,
 }
 
 impl Default for Typewriter {
     fn default() -> Self {
         Self {
             character_name: default(),
-            current_text: default(),
-            graphemes_left: default(),
+            graphemes: default(),
+            written: default(),
             elapsed: default(),
-            start: Instant::now(),
             last_finished: default(),
+            completing: default(),
+            skipping_seen_line: default(),
+            voice: default(),
             // We set this high so we can see when things go wrong.
             // The speed in game should never be this number!
             current_speed: 100.0,
@@ -50,72 +54,103 @@ impl Default for Typewriter {
     }
 }
 
+/// Short stops after punctuation, in seconds for a speaker of average speed.
+fn punctuation_pause(grapheme: &str, next: Option<&str>) -> f32 {
+    match grapheme {
+        "?" => 0.35,
+        ":" => 0.3,
+        // Only stop after the last dot of an ellipsis.
+        "." if !next.is_some_and(|next| next.starts_with('.')) => 0.2,
+        "," => 0.1,
+        _ => 0.0,
+    }
+}
+
 impl Typewriter {
     pub fn set_line(&mut self, line: &LocalizedLine) {
         *self = Self {
             character_name: line.character_name().map(|s| s.to_string()),
-            current_text: String::new(),
-            graphemes_left: line
-                .text_without_character_name()
-                .graphemes(true)
-                .map(|s| s.to_string())
-                .collect(),
-            // This fn can get called AFTER setting writer speed
+            graphemes: marked_graphemes(line),
+            // This fn can get called AFTER setting writer voice
             current_speed: self.current_speed,
+            voice: self.voice.clone(),
             ..default()
         };
     }
 
     pub fn is_finished(&self) -> bool {
-        self.graphemes_left.is_empty() && !self.current_text.is_empty()
+        self.written == self.graphemes.len() && self.written > 0
+    }
+
+    /// Show the whole line right away, for players that read faster than it's written.
+    pub fn complete(&mut self) {
+        self.completing = true;
+    }
+
+    /// Complete the line and let it continue on its own shortly after.
+    pub fn skip_seen_line(&mut self) {
+        self.completing = true;
+        self.skipping_seen_line = true;
+    }
+
+    pub fn is_skipping_seen_line(&self) -> bool {
+        self.skipping_seen_line
     }
 
-    fn update_current_text(&mut self) -> String {
+    /// Number of graphemes of the whole line, without the character name.
+    pub fn line_length(&self) -> usize {
+        self.graphemes.len()
+    }
+
+    /// Markup speeds are given for the average speaker, slower speakers stay slower.
+    fn speed(&self, grapheme: &MarkedGrapheme) -> f32 {
+        grapheme.speed.map_or(self.current_speed, |speed| {
+            speed * self.current_speed / AVERAGE_SPEED
+        })
+    }
+
+    /// Advance the typewriter by `delta` seconds of game time,
+    /// so that it stands still while the game is paused.
+    fn update_current_text(&mut self, delta: f32) -> String {
         if self.is_finished() {
             return String::new();
         }
-        self.elapsed += self.start.elapsed().as_secs_f32();
-        self.start = Instant::now();
-
-        let calculated_graphemes = (self.current_speed * self.elapsed).floor() as usize;
-        let graphemes_left = self.graphemes_left.len();
-        let grapheme_length_to_take = (calculated_graphemes).min(graphemes_left);
-
-        self.elapsed -= grapheme_length_to_take as f32 / self.current_speed;
-        let graphemes_to_take = self
-            .graphemes_left
-            .drain(..grapheme_length_to_take)
-            .collect::<Vec<String>>()
-            .concat();
+        self.elapsed += delta;
 
         let multiplier = AVERAGE_SPEED / self.current_speed;
-        if graphemes_to_take.contains('?') {
-            self.elapsed -= 0.35 * multiplier;
-        } else if graphemes_to_take.contains(':') {
-            self.elapsed -= 0.3 * multiplier;
-        } else if graphemes_to_take.contains('.') {
-            if let Some(index) = graphemes_to_take.chars().rev().position(|c| c == '.') {
-                if index + 1 < graphemes_to_take.len()
-                    || !self.graphemes_left.is_empty() && !self.graphemes_left[0].starts_with('.')
-                {
-                    self.elapsed -= 0.2 * multiplier;
-                }
+        let mut written_text = String::new();
+        while let Some(grapheme) = self.graphemes.get(self.written) {
+            let cost = if self.completing {
+                0.0
+            } else {
+                grapheme.pause * multiplier + 1.0 / self.speed(grapheme)
+            };
+            if self.elapsed < cost {
+                break;
+            }
+            self.elapsed -= cost;
+            self.written += 1;
+            written_text += &grapheme.text;
+
+            if !self.completing {
+                let next = self.graphemes.get(self.written).map(|g| g.text.as_str());
+                self.elapsed -= punctuation_pause(&grapheme.text, next)
+                    * self.voice.punctuation_pause_multiplier(&grapheme.text)
+                    * multiplier;
             }
-        } else if graphemes_to_take.contains(',') {
-            self.elapsed -= 0.1 * multiplier;
         }
-        self.current_text += &graphemes_to_take;
-        graphemes_to_take.to_string()
+        written_text
     }
 }
 
 fn write_text(
-    assets: Res<GameAssets>,
+    time: Res<Time>,
+    locale: Res<Locale>,
     mut typewriter: ResMut<Typewriter>,
-    mut q_text: Query<&mut Text, With<DialogueContent>>,
+    mut q_text: Query<(&mut Text, &mut TextEffects), With<DialogueContent>>,
     mut ev_play_blip: EventWriter<PlayBlipEvent>,
 ) {
-    let mut text = match q_text.get_single_mut() {
+    let (mut text, mut effects) = match q_text.get_single_mut() {
         Ok(r) => r,
         Err(_) => return,
     };
@@ -124,16 +159,17 @@ fn write_text(
         return;
     }
 
-    let added_text = typewriter.update_current_text();
+    // A completed line would play all of its blips at once otherwise.
+    // Nothing gets written during pauses, so they are silent too.
+    let completing = typewriter.completing;
+    let added_text = typewriter.update_current_text(time.delta_seconds());
 
-    if !added_text.is_empty() && &added_text != " " {
+    if !completing && !added_text.trim().is_empty() {
         ev_play_blip.send(PlayBlipEvent::new(
             &typewriter.character_name.clone().unwrap_or_default(),
         ));
     }
-
-    let rest = typewriter.graphemes_left.join("");
-    *text = create_dialogue_text(&typewriter.current_text, rest, &assets);
+    (*text, *effects) = create_dialogue_text(&typewriter.graphemes, typewriter.written, &locale);
 }
 
 fn show_continue(
@@ -163,32 +199,20 @@ fn send_finished_event(
     }
 }
 
-fn set_writer_speed(
+fn set_writer_voice(
     debug_active: Res<DebugActive>,
+    characters: Res<Characters>,
     mut typewriter: ResMut<Typewriter>,
     mut ev_present_line: EventReader<PresentLineEvent>,
 ) {
     for ev in ev_present_line.read() {
-        if **debug_active {
-            typewriter.current_speed = DEBUG_SPEED;
-            continue;
-        }
-
-        let name = ev
-            .line
-            .character_name()
-            .unwrap_or_default()
-            .trim_start_matches('_');
-        let maybe_npc = NpcDialogue::from_str(name);
-        let speed = if let Ok(npc) = maybe_npc {
-            match npc {
-                NpcDialogue::Ami => 18.0,
-                NpcDialogue::Ima => 18.0,
-            }
+        let voice = characters.speaker(ev.line.character_name());
+        typewriter.current_speed = if **debug_active {
+            DEBUG_SPEED
         } else {
-            20.0
+            voice.typing_speed
         };
-        typewriter.current_speed = speed;
+        typewriter.voice = voice.clone();
     }
 }
 
@@ -202,7 +226,7 @@ impl Plugin for DialogueTypewriterPlugin {
                 send_finished_event,
                 write_text,
                 show_continue,
-                set_writer_speed,
+                set_writer_voice,
             )
                 .chain()
                 .after(YarnSpinnerSystemSet)
@@ -210,6 +234,7 @@ impl Plugin for DialogueTypewriterPlugin {
                 .run_if(not(in_state(GameState::AssetLoading))),
         )
         .init_resource::<Typewriter>()
+        .add_systems(OnEnter(GameState::Intro), reset_resource::<Typewriter>)
         .add_event::<TypewriterFinished>();
     }
 }
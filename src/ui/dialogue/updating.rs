@@ -1,26 +1,37 @@
-use std::str::FromStr;
-
 use bevy::prelude::*;
-use bevy::render::texture::TRANSPARENT_IMAGE_HANDLE;
+use bevy_trickfilm::prelude::*;
 use bevy_yarnspinner::{events::*, prelude::*};
 
-use crate::npc::{npc_character_icon, NpcDialogue};
+use crate::npc::characters::Characters;
 use crate::player::input::PlayerInput;
-use crate::{GameAssets, GameState};
+use crate::{locale::Locale, GameState};
 
+use super::advance::{DialogueSettings, SeenLines};
 use super::runner::RunnerFlags;
 use super::spawn::{DialogueCharacterIcon, DialogueContinueNode, DialogueNameNode};
 use super::typewriter::Typewriter;
 use super::DialogueViewSystemSet;
 
-fn convert_name(name: &str) -> String {
+pub fn convert_name(name: &str, characters: &Characters, locale: &Locale) -> String {
     if name.starts_with('_') {
-        return "???".to_string();
+        return locale.text("dialogue.unknown_speaker");
+    }
+    match characters.get(name) {
+        Some(profile) => profile.display_name.clone(),
+        None => name.to_string(),
     }
-    name.to_string()
+}
+
+/// The neutral icon of whoever speaks the line, narrator lines have none.
+pub fn character_icon(characters: &Characters, line: &LocalizedLine) -> Handle<Image> {
+    characters.speaker(line.character_name()).portrait.clone()
 }
 
 fn present_line(
+    locale: Res<Locale>,
+    characters: Res<Characters>,
+    settings: Res<DialogueSettings>,
+    mut seen_lines: ResMut<SeenLines>,
     mut typewriter: ResMut<Typewriter>,
     mut q_name_text: Query<&mut Text, With<DialogueNameNode>>,
     mut q_runner_flags: Query<&mut RunnerFlags>,
@@ -32,9 +43,16 @@ fn present_line(
     };
 
     for ev in ev_present_line.read() {
-        let name = convert_name(ev.line.character_name().unwrap_or_default());
+        let name = convert_name(
+            ev.line.character_name().unwrap_or_default(),
+            &characters,
+            &locale,
+        );
         name_text.sections[0].value = name;
         typewriter.set_line(&ev.line);
+        if seen_lines.see(&ev.line) && settings.skip_seen_lines {
+            typewriter.skip_seen_line();
+        }
 
         for mut flags in &mut q_runner_flags {
             flags.line = Some(ev.line.clone());
@@ -42,48 +60,74 @@ fn present_line(
     }
 }
 
-fn continue_dialogue(
+pub fn continue_runners(
+    q_dialogue_runners: &mut Query<&mut DialogueRunner>,
+    q_continue_visibility: &mut Query<&mut Visibility, With<DialogueContinueNode>>,
+) {
+    for mut dialogue_runner in q_dialogue_runners {
+        if !dialogue_runner.is_waiting_for_option_selection() && dialogue_runner.is_running() {
+            dialogue_runner.continue_in_next_update();
+            *q_continue_visibility.single_mut() = Visibility::Hidden;
+        }
+    }
+}
+
+pub fn continue_dialogue(
     input: Res<PlayerInput>,
-    typewriter: Res<Typewriter>,
-    mut q_dialogue_runners: Query<(&mut DialogueRunner, &RunnerFlags)>,
+    mut typewriter: ResMut<Typewriter>,
+    mut q_dialogue_runners: Query<&mut DialogueRunner>,
     mut q_continue_visibility: Query<&mut Visibility, With<DialogueContinueNode>>,
 ) {
-    if input.dialogue_continue && !typewriter.is_finished() {
+    if !input.dialogue_continue {
         return;
     }
 
-    if !input.dialogue_continue {
+    // The first press only completes the line, the next one continues.
+    if !typewriter.is_finished() {
+        typewriter.complete();
         return;
     }
 
-    for (mut dialogue_runner, _) in &mut q_dialogue_runners {
-        if !dialogue_runner.is_waiting_for_option_selection() && dialogue_runner.is_running() {
-            dialogue_runner.continue_in_next_update();
-            *q_continue_visibility.single_mut() = Visibility::Hidden;
-        }
-    }
+    continue_runners(&mut q_dialogue_runners, &mut q_continue_visibility);
 }
 
-fn update_dialogue_character_icon(
-    assets: Res<GameAssets>,
-    mut q_character_icon: Query<&mut UiImage, With<DialogueCharacterIcon>>,
+/// Tags of the line like `#smile` pick the expression, lines without one show the neutral icon.
+pub(super) fn update_dialogue_character_icon(
+    mut commands: Commands,
+    characters: Res<Characters>,
+    mut q_character_icon: Query<(Entity, &mut UiImage), With<DialogueCharacterIcon>>,
     mut ev_present_line: EventReader<PresentLineEvent>,
 ) {
-    let mut image = match q_character_icon.get_single_mut() {
+    let (entity, mut image) = match q_character_icon.get_single_mut() {
         Ok(r) => r,
         Err(_) => return,
     };
 
     for ev in ev_present_line.read() {
-        let texture = match ev.line.character_name() {
-            Some(name) => npc_character_icon(
-                &assets,
-                &NpcDialogue::from_str(name.trim_start_matches('_')).unwrap_or_default(),
-            ),
-            None => TRANSPARENT_IMAGE_HANDLE,
+        let profile = characters.speaker(ev.line.character_name());
+        let (texture, layout, animation) = match profile.expression(&ev.line.metadata) {
+            Some(r) => (r.image.clone(), r.layout.clone(), r.animation.clone()),
+            None => (profile.portrait.clone(), None, None),
         };
-
         image.texture = texture;
+
+        let layout = match layout {
+            Some(r) => r,
+            None => {
+                commands
+                    .entity(entity)
+                    .remove::<(TextureAtlas, AnimationPlayer2D)>();
+                continue;
+            }
+        };
+        // Sprite sheets without an animation stay on their first frame.
+        let mut animator = AnimationPlayer2D::default();
+        if let Some(animation) = animation {
+            animator.play(animation).repeat();
+        }
+        commands
+            .entity(entity)
+            .insert((TextureAtlas { layout, index: 0 }, animator));
     }
 }
 
@@ -0,0 +1,228 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use bevy::prelude::*;
+use bevy_yarnspinner::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    aspect::{Aspect, Combiner, Socket, SocketMemory},
+    npc::narrator::evaluate_aspect,
+    run::reset_resource,
+    world::CurrentLevel,
+    GameState,
+};
+
+pub const COMBINATIONS_MADE: &str = "$combinations_made";
+pub const BALANCE_SCORE: &str = "$balance_score";
+pub const LAST_COMBINED_ASPECT: &str = "$last_combined_aspect";
+
+/// The name of the variable that tells whether `aspect` is in any socket, e.g. `$aspect_joy_present`.
+pub fn aspect_present_variable(aspect: Aspect) -> String {
+    format!("$aspect_{}_present", aspect.to_string().to_lowercase())
+}
+
+/// A yarn variable as it's written to the save file.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum SavedVariable {
+    Number(f32),
+    String(String),
+    Boolean(bool),
+}
+
+impl From<YarnValue> for SavedVariable {
+    fn from(value: YarnValue) -> Self {
+        match value {
+            YarnValue::Number(n) => SavedVariable::Number(n),
+            YarnValue::String(s) => SavedVariable::String(s),
+            YarnValue::Boolean(b) => SavedVariable::Boolean(b),
+        }
+    }
+}
+
+impl From<SavedVariable> for YarnValue {
+    fn from(value: SavedVariable) -> Self {
+        match value {
+            SavedVariable::Number(n) => YarnValue::Number(n),
+            SavedVariable::String(s) => YarnValue::String(s),
+            SavedVariable::Boolean(b) => YarnValue::Boolean(b),
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct Variables {
+    /// Mirrored from the game state, yarn can only read these.
+    game: HashMap<String, YarnValue>,
+    /// Set from within yarn, they stay around for the whole run.
+    yarn: HashMap<String, YarnValue>,
+}
+
+/// The variable storage all dialogue runners share.
+/// It exposes the game state to yarn and keeps the variables yarn sets across dialogues.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct DialogueVariables(Arc<RwLock<Variables>>);
+
+impl DialogueVariables {
+    fn read(&self) -> RwLockReadGuard<'_, Variables> {
+        self.0.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Variables> {
+        self.0.write().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// A handle for a dialogue runner, it shares the variables with this resource.
+    pub fn storage(&self) -> Box<dyn VariableStorage> {
+        Box::new(self.clone())
+    }
+
+    pub fn set_game_variables(&self, game: HashMap<String, YarnValue>) {
+        self.write().game = game;
+    }
+
+    pub fn saved_variables(&self) -> HashMap<String, SavedVariable> {
+        self.read()
+            .yarn
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone().into()))
+            .collect()
+    }
+
+    pub fn load_saved_variables(&self, saved: &HashMap<String, SavedVariable>) {
+        self.write().yarn = saved
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone().into()))
+            .collect();
+    }
+}
+
+impl VariableStorage for DialogueVariables {
+    fn clone_shallow(&self) -> Box<dyn VariableStorage> {
+        self.storage()
+    }
+
+    fn set(&mut self, name: String, value: YarnValue) -> Result<(), VariableStorageError> {
+        if !name.starts_with('$') {
+            return Err(VariableStorageError::InvalidVariableName { name });
+        }
+
+        let mut variables = self.write();
+        if variables.game.contains_key(&name) {
+            error!("yarn tried to set '{}', but it's read only", name);
+            return Err(VariableStorageError::InvalidVariableName { name });
+        }
+        variables.yarn.insert(name, value);
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<YarnValue, VariableStorageError> {
+        let variables = self.read();
+        match variables
+            .game
+            .get(name)
+            .or_else(|| variables.yarn.get(name))
+        {
+            Some(r) => Ok(r.clone()),
+            None => Err(VariableStorageError::VariableNotFound {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<(), VariableStorageError> {
+        for (name, value) in values {
+            self.set(name, value)?;
+        }
+        Ok(())
+    }
+
+    fn variables(&self) -> HashMap<String, YarnValue> {
+        let variables = self.read();
+        let mut all = variables.yarn.clone();
+        all.extend(variables.game.clone());
+        all
+    }
+
+    /// Only clears the variables set by yarn, the game ones are mirrored from the world anyways.
+    fn clear(&mut self) {
+        self.write().yarn.clear();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The read only variables yarn sees, given the combiner and the aspects in all sockets.
+pub fn game_variables(combiner: &Combiner, aspects: &[Aspect]) -> HashMap<String, YarnValue> {
+    let mut variables = HashMap::new();
+    variables.insert(
+        COMBINATIONS_MADE.to_string(),
+        YarnValue::Number(combiner.combined_aspects.len() as f32),
+    );
+    variables.insert(
+        BALANCE_SCORE.to_string(),
+        YarnValue::Number(aspects.iter().map(|a| evaluate_aspect(*a)).sum::<i32>() as f32),
+    );
+    variables.insert(
+        LAST_COMBINED_ASPECT.to_string(),
+        YarnValue::String(combiner.last_combined_aspect.to_string()),
+    );
+    for aspect in Aspect::iter().filter(|a| *a != Aspect::NotImplemented) {
+        variables.insert(
+            aspect_present_variable(aspect),
+            YarnValue::Boolean(aspects.contains(&aspect)),
+        );
+    }
+    variables
+}
+
+fn sync_game_variables(
+    variables: Res<DialogueVariables>,
+    combiner: Res<Combiner>,
+    current_level: Res<CurrentLevel>,
+    socket_memory: Res<SocketMemory>,
+    q_sockets: Query<(&Transform, &Socket)>,
+) {
+    let aspects = socket_memory
+        .merged(&current_level.0, q_sockets.iter())
+        .into_values()
+        .collect::<Vec<Aspect>>();
+    variables.set_game_variables(game_variables(&combiner, &aspects));
+}
+
+fn sockets_changed(q_sockets: Query<(), Changed<Socket>>) -> bool {
+    !q_sockets.is_empty()
+}
+
+pub struct DialogueVariablesPlugin;
+
+impl Plugin for DialogueVariablesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DialogueVariables>()
+            .add_systems(
+                OnEnter(GameState::Intro),
+                reset_resource::<DialogueVariables>,
+            )
+            // Runners that get spawned this frame only start in the next one,
+            // so syncing in `PostUpdate` is early enough for them to see the new state.
+            .add_systems(
+                PostUpdate,
+                sync_game_variables.run_if(
+                    resource_changed::<DialogueVariables>
+                        .or_else(resource_changed::<Combiner>)
+                        .or_else(resource_changed::<SocketMemory>)
+                        .or_else(sockets_changed),
+                ),
+            );
+    }
+}
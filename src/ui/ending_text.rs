@@ -2,10 +2,15 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::{GameAssets, GameState};
+use crate::{
+    locale::Locale,
+    player::input_map::{InputAction, InputDevice, InputMap},
+    run::RunScoped,
+    GameState,
+};
 
 #[derive(Component)]
-struct WriteableText {
+pub struct WriteableText {
     written_text: String,
     text_left: String,
     timer: Timer,
@@ -23,59 +28,124 @@ impl WriteableText {
     }
 }
 
-fn text_style_standard(assets: &Res<GameAssets>) -> TextStyle {
+fn text_style_standard(locale: &Res<Locale>) -> TextStyle {
     TextStyle {
         font_size: 100.0,
         color: Color::WHITE,
-        font: assets.silver_font.clone(),
+        font: locale.font(),
     }
 }
 
-fn spawn_fin_text(commands: &mut Commands, assets: &Res<GameAssets>) -> Entity {
+fn spawn_fin_text(commands: &mut Commands, locale: &Res<Locale>) -> Entity {
     commands
         .spawn((
             TextBundle {
-                text: Text::from_section("", text_style_standard(assets)),
+                text: Text::from_section("", text_style_standard(locale)),
                 z_index: ZIndex::Local(1),
                 ..default()
             },
-            WriteableText::new("FIN", 0.25, 1.5),
+            WriteableText::new(&locale.text("ending.fin"), 0.25, 1.5),
         ))
         .id()
 }
 
-fn spawn_thanks_text(commands: &mut Commands, assets: &Res<GameAssets>) -> Entity {
+fn spawn_thanks_text(commands: &mut Commands, locale: &Res<Locale>) -> Entity {
     commands
         .spawn((
             TextBundle {
-                text: Text::from_section("", text_style_standard(assets)),
+                text: Text::from_section("", text_style_standard(locale)),
                 z_index: ZIndex::Local(1),
                 ..default()
             },
-            WriteableText::new("Thanks for Playing", 0.1, 5.0),
+            WriteableText::new(&locale.text("ending.thanks"), 0.1, 5.0),
         ))
         .id()
 }
 
-fn spawn_final_texts(mut commands: Commands, assets: Res<GameAssets>) {
-    let header_text = spawn_fin_text(&mut commands, &assets);
-    let body_text = spawn_thanks_text(&mut commands, &assets);
+fn spawn_gallery_hint_text(
+    commands: &mut Commands,
+    locale: &Res<Locale>,
+    input_map: &InputMap,
+    input_device: InputDevice,
+) -> Entity {
+    let label = input_map.hint(InputAction::DialogueConfirm, input_device);
 
     commands
-        .spawn((NodeBundle {
-            style: Style {
-                position_type: PositionType::Absolute,
-                top: Val::Percent(40.0),
-                width: Val::Percent(100.0),
-                flex_direction: FlexDirection::Column,
-                row_gap: Val::Vh(25.0),
-                align_items: AlignItems::Center,
+        .spawn((
+            TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 40.0,
+                        color: Color::WHITE,
+                        font: locale.font(),
+                    },
+                ),
+                z_index: ZIndex::Local(1),
                 ..default()
             },
-            z_index: ZIndex::Global(1001),
-            ..default()
-        },))
+            WriteableText::new(
+                &locale.text("ending.gallery_hint").replace("{key}", &label),
+                0.05,
+                8.0,
+            ),
+        ))
+        .id()
+}
+
+/// The gallery can only be opened once all the final texts are on screen.
+pub fn ending_texts_written(q_writable_texts: Query<&WriteableText>) -> bool {
+    !q_writable_texts.is_empty()
+        && q_writable_texts
+            .iter()
+            .all(|writeable_text| writeable_text.text_left.is_empty())
+}
+
+fn spawn_final_texts(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    input_map: Res<InputMap>,
+    input_device: Res<InputDevice>,
+) {
+    let header_text = spawn_fin_text(&mut commands, &locale);
+    let body_text = spawn_thanks_text(&mut commands, &locale);
+    let hint_text = spawn_gallery_hint_text(&mut commands, &locale, &input_map, *input_device);
+
+    commands
+        .spawn((
+            RunScoped,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(40.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Vh(25.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(1001),
+                ..default()
+            },
+        ))
         .push_children(&[header_text, body_text]);
+
+    commands
+        .spawn((
+            RunScoped,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Percent(5.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(1001),
+                ..default()
+            },
+        ))
+        .add_child(hint_text);
 }
 
 fn write_texts(time: Res<Time>, mut q_writable_texts: Query<(&mut Text, &mut WriteableText)>) {
@@ -0,0 +1,298 @@
+use bevy::{color::palettes::css::GRAY, prelude::*};
+
+use crate::{
+    aspect::{Aspect, AspectRecipes},
+    locale::Locale,
+    npc::endings::EndingRules,
+    player::{
+        input::PlayerInput,
+        input_map::{InputAction, InputDevice, InputMap},
+    },
+    run::{reset_resource, RunScoped},
+    save::Unlocks,
+    GameState,
+};
+
+use super::ending_text::ending_texts_written;
+
+const BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.9);
+
+#[derive(Resource, Default)]
+pub struct Gallery {
+    open: bool,
+}
+
+impl Gallery {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+/// Same as with the settings, the press that closes the gallery shouldn't pause the game.
+pub fn gallery_closed(gallery: Res<Gallery>, q_screens: Query<(), With<GalleryScreen>>) -> bool {
+    !gallery.is_open() && q_screens.is_empty()
+}
+
+#[derive(Component)]
+pub struct GalleryScreen;
+
+/// Split a node title like `BadEndingTooPositive` into words, `Bad Ending Too Positive`.
+/// Used for endings that have no name in the string tables.
+pub fn ending_display_name(node: &str) -> String {
+    let mut name = String::new();
+    for (i, c) in node.chars().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            name.push(' ');
+        }
+        name.push(c);
+    }
+    name
+}
+
+/// The ending nodes in the order of the rules, each one only once.
+fn ending_nodes(rules: &EndingRules) -> Vec<String> {
+    let mut nodes = Vec::new();
+    for rule in rules.rules() {
+        if !nodes.contains(&rule.node) {
+            nodes.push(rule.node.clone());
+        }
+    }
+    nodes
+}
+
+/// All aspects that can be the result of a combination, each one only once.
+fn combination_results(recipes: &AspectRecipes) -> Vec<Aspect> {
+    let mut results = Vec::new();
+    for recipe in recipes.recipes() {
+        if !results.contains(&recipe.result) {
+            results.push(recipe.result);
+        }
+    }
+    results
+}
+
+fn spawn_column(
+    commands: &mut Commands,
+    locale: &Res<Locale>,
+    title: &str,
+    entries: Vec<(String, bool)>,
+) -> Entity {
+    let unlocked = entries.iter().filter(|(_, unlocked)| *unlocked).count();
+    let header = commands
+        .spawn(TextBundle::from_section(
+            format!("{}  {}/{}", title, unlocked, entries.len()),
+            TextStyle {
+                font: locale.font(),
+                font_size: 50.0,
+                color: Color::WHITE,
+            },
+        ))
+        .id();
+
+    let mut children = vec![header];
+    for (name, unlocked) in entries {
+        let (text, color) = if unlocked {
+            (name, Color::WHITE)
+        } else {
+            (locale.text("ui.locked"), GRAY.into())
+        };
+        children.push(
+            commands
+                .spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font: locale.font(),
+                        font_size: 32.0,
+                        color,
+                    },
+                ))
+                .id(),
+        );
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&children)
+        .id()
+}
+
+/// The bound keys of the device the player uses, so that rebinding shows up here too.
+fn footer_text(locale: &Locale, input_map: &InputMap, input_device: InputDevice) -> String {
+    locale
+        .text("gallery.footer")
+        .replace(
+            "{escape}",
+            &input_map.hint(InputAction::Escape, input_device),
+        )
+        .replace(
+            "{confirm}",
+            &input_map.hint(InputAction::DialogueConfirm, input_device),
+        )
+}
+
+fn spawn_gallery(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    input_map: Res<InputMap>,
+    input_device: Res<InputDevice>,
+    unlocks: Res<Unlocks>,
+    ending_rules: Res<EndingRules>,
+    recipes: Res<AspectRecipes>,
+) {
+    let header = commands
+        .spawn(TextBundle::from_section(
+            locale.text("gallery.header"),
+            TextStyle {
+                font: locale.font(),
+                font_size: 80.0,
+                color: Color::WHITE,
+            },
+        ))
+        .id();
+
+    let endings = ending_nodes(&ending_rules)
+        .into_iter()
+        .map(|node| {
+            let unlocked = unlocks.has_ending(&node);
+            let name = match locale.get(&format!("ending.{}", node)) {
+                Some(r) => r.to_string(),
+                None => ending_display_name(&node),
+            };
+            (name, unlocked)
+        })
+        .collect();
+    let combinations = combination_results(&recipes)
+        .into_iter()
+        .map(|aspect| (locale.aspect_name(aspect), unlocks.has_combination(aspect)))
+        .collect();
+
+    let endings_column = spawn_column(
+        &mut commands,
+        &locale,
+        &locale.text("gallery.endings"),
+        endings,
+    );
+    let combinations_column = spawn_column(
+        &mut commands,
+        &locale,
+        &locale.text("gallery.combinations"),
+        combinations,
+    );
+    let columns = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexStart,
+                column_gap: Val::Px(120.0),
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&[endings_column, combinations_column])
+        .id();
+
+    let footer = commands
+        .spawn(TextBundle::from_section(
+            footer_text(&locale, &input_map, *input_device),
+            TextStyle {
+                font: locale.font(),
+                font_size: 30.0,
+                color: GRAY.into(),
+            },
+        ))
+        .id();
+
+    commands
+        .spawn((
+            RunScoped,
+            GalleryScreen,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(30.0),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(1100),
+                ..default()
+            },
+        ))
+        .push_children(&[header, columns, footer]);
+}
+
+fn despawn_gallery(mut commands: Commands, q_screens: Query<Entity, With<GalleryScreen>>) {
+    for entity in &q_screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn open_gallery(player_input: Res<PlayerInput>, mut gallery: ResMut<Gallery>) {
+    if player_input.dialogue_confirm || player_input.select_socket {
+        gallery.open();
+    }
+}
+
+fn close_gallery(player_input: Res<PlayerInput>, mut gallery: ResMut<Gallery>) {
+    if player_input.escape || player_input.dialogue_confirm {
+        gallery.close();
+    }
+}
+
+/// The gallery only reacts to input once it's on screen,
+/// so that the press that opened it doesn't close it right away.
+fn menu_shown(gallery: Res<Gallery>, q_screens: Query<(), With<GalleryScreen>>) -> bool {
+    gallery.is_open() && !q_screens.is_empty()
+}
+
+fn menu_opened(gallery: Res<Gallery>, q_screens: Query<(), With<GalleryScreen>>) -> bool {
+    gallery.is_open() && q_screens.is_empty()
+}
+
+fn menu_closed(gallery: Res<Gallery>, q_screens: Query<(), With<GalleryScreen>>) -> bool {
+    !gallery.is_open() && !q_screens.is_empty()
+}
+
+pub struct GalleryPlugin;
+
+impl Plugin for GalleryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Gallery>()
+            .add_systems(OnEnter(GameState::Intro), reset_resource::<Gallery>)
+            .add_systems(OnEnter(GameState::Title), reset_resource::<Gallery>)
+            // Spawning comes last, the press that opened the gallery can't also close it.
+            .add_systems(
+                Update,
+                (
+                    open_gallery
+                        .run_if(gallery_closed)
+                        .run_if(ending_texts_written),
+                    close_gallery.run_if(menu_shown),
+                    despawn_gallery.run_if(menu_closed),
+                    spawn_gallery.run_if(menu_opened),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
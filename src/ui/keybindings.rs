@@ -0,0 +1,277 @@
+use bevy::{
+    color::palettes::css::{GRAY, YELLOW},
+    input::InputSystem,
+    prelude::*,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    locale::Locale,
+    player::input_map::{Binding, InputAction, InputMap},
+    GameState,
+};
+
+// The menu itself uses fixed keys so that you can't lock yourself out with bad bindings.
+const TOGGLE_KEY: KeyCode = KeyCode::F1;
+const BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.85);
+
+#[derive(Resource, Default)]
+pub struct KeybindingsMenu {
+    open: bool,
+    selected: usize,
+    listening: bool,
+}
+
+impl KeybindingsMenu {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.selected = 0;
+        self.listening = false;
+    }
+}
+
+pub fn keybindings_menu_closed(menu: Res<KeybindingsMenu>) -> bool {
+    !menu.is_open()
+}
+
+#[derive(Component)]
+struct KeybindingsScreen;
+
+#[derive(Component)]
+struct KeybindingsRow(usize);
+
+/// All actions plus the trailing "reset to defaults" row.
+fn row_count() -> usize {
+    InputAction::iter().count() + 1
+}
+
+fn text_style_row(locale: &Res<Locale>) -> TextStyle {
+    TextStyle {
+        font: locale.font(),
+        font_size: 40.0,
+        color: GRAY.into(),
+    }
+}
+
+fn row_text(menu: &KeybindingsMenu, input_map: &InputMap, locale: &Locale, index: usize) -> String {
+    let action = match InputAction::iter().nth(index) {
+        Some(r) => r,
+        None => return locale.text("keybindings.reset"),
+    };
+
+    let bindings = if menu.listening && menu.selected == index {
+        locale.text("keybindings.listening")
+    } else {
+        input_map
+            .bindings(action)
+            .iter()
+            .map(|b| b.label())
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    format!(
+        "{}: {}",
+        locale.text(&format!("action.{}", action)),
+        bindings
+    )
+}
+
+fn toggle_menu(keys: Res<ButtonInput<KeyCode>>, mut menu: ResMut<KeybindingsMenu>) {
+    if !keys.just_pressed(TOGGLE_KEY) || menu.listening {
+        return;
+    }
+
+    if menu.open {
+        menu.open = false;
+    } else {
+        menu.open();
+    }
+}
+
+fn spawn_menu(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    menu: Res<KeybindingsMenu>,
+    input_map: Res<InputMap>,
+) {
+    let mut rows = Vec::new();
+    for index in 0..row_count() {
+        rows.push(
+            commands
+                .spawn((
+                    KeybindingsRow(index),
+                    TextBundle::from_section(
+                        row_text(&menu, &input_map, &locale, index),
+                        text_style_row(&locale),
+                    ),
+                ))
+                .id(),
+        );
+    }
+
+    let header = commands
+        .spawn(TextBundle::from_section(
+            locale.text("keybindings.header"),
+            TextStyle {
+                font: locale.font(),
+                font_size: 60.0,
+                color: Color::WHITE,
+            },
+        ))
+        .id();
+    let footer = commands
+        .spawn(TextBundle::from_section(
+            locale.text("keybindings.footer"),
+            TextStyle {
+                font: locale.font(),
+                font_size: 30.0,
+                color: GRAY.into(),
+            },
+        ))
+        .id();
+
+    commands
+        .spawn((
+            KeybindingsScreen,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(500),
+                ..default()
+            },
+        ))
+        .add_child(header)
+        .push_children(&rows)
+        .add_child(footer);
+}
+
+fn despawn_menu(mut commands: Commands, q_screens: Query<Entity, With<KeybindingsScreen>>) {
+    for entity in &q_screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_menu(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut menu: ResMut<KeybindingsMenu>,
+    mut input_map: ResMut<InputMap>,
+) {
+    if menu.listening {
+        if keys.just_pressed(KeyCode::Escape) {
+            menu.listening = false;
+            return;
+        }
+
+        let binding = match keys.get_just_pressed().next() {
+            Some(key) => Binding::Key(*key),
+            None => match mouse_buttons.get_just_pressed().next() {
+                Some(button) => Binding::Mouse(*button),
+                None => match gamepad_buttons.get_just_pressed().next() {
+                    Some(button) => Binding::Gamepad(button.button_type),
+                    None => return,
+                },
+            },
+        };
+        if let Some(action) = InputAction::iter().nth(menu.selected) {
+            input_map.rebind(action, binding);
+            input_map.save();
+        }
+        menu.listening = false;
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        menu.open = false;
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + row_count() - 1) % row_count();
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % row_count();
+    }
+
+    if keys.just_pressed(KeyCode::Enter) {
+        if menu.selected == row_count() - 1 {
+            input_map.reset();
+            input_map.save();
+        } else {
+            menu.listening = true;
+        }
+    }
+}
+
+fn update_rows(
+    menu: Res<KeybindingsMenu>,
+    input_map: Res<InputMap>,
+    locale: Res<Locale>,
+    mut q_rows: Query<(&KeybindingsRow, &mut Text)>,
+) {
+    for (row, mut text) in &mut q_rows {
+        text.sections[0].value = row_text(&menu, &input_map, &locale, row.0);
+        text.sections[0].style.color = if row.0 != menu.selected {
+            GRAY.into()
+        } else if menu.listening {
+            YELLOW.into()
+        } else {
+            Color::WHITE
+        };
+    }
+}
+
+/// The menu only reacts to input once it's on screen,
+/// so that the key press that opened it doesn't also start a rebind.
+fn menu_shown(menu: Res<KeybindingsMenu>, q_screens: Query<(), With<KeybindingsScreen>>) -> bool {
+    menu.is_open() && !q_screens.is_empty()
+}
+
+fn menu_opened(menu: Res<KeybindingsMenu>, q_screens: Query<(), With<KeybindingsScreen>>) -> bool {
+    menu.is_open() && q_screens.is_empty()
+}
+
+fn menu_closed(menu: Res<KeybindingsMenu>, q_screens: Query<(), With<KeybindingsScreen>>) -> bool {
+    !menu.is_open() && !q_screens.is_empty()
+}
+
+pub struct KeybindingsPlugin;
+
+impl Plugin for KeybindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeybindingsMenu>()
+            .add_systems(
+                PreUpdate,
+                toggle_menu
+                    .after(InputSystem)
+                    .run_if(not(in_state(GameState::AssetLoading))),
+            )
+            .add_systems(
+                Update,
+                (
+                    navigate_menu.run_if(menu_shown),
+                    spawn_menu.run_if(menu_opened),
+                    despawn_menu.run_if(menu_closed),
+                    update_rows.run_if(
+                        resource_changed::<KeybindingsMenu>.or_else(resource_changed::<InputMap>),
+                    ),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
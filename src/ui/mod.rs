@@ -1,8 +1,19 @@
-mod dialogue;
+mod codex;
+pub mod dialogue;
 mod ending_text;
+mod gallery;
+mod keybindings;
+mod pause_menu;
 mod screens;
+mod settings;
 mod vignette;
 
+pub use codex::{codex_menu_closed, CodexMenu};
+pub use dialogue::log::dialogue_log_closed;
+pub use keybindings::keybindings_menu_closed;
+pub use screens::{FadeScreen, ScreenFaded};
+pub use settings::{settings_menu_closed, SettingsMenu};
+
 use bevy::prelude::*;
 
 pub struct UiPlugin;
@@ -14,6 +25,11 @@ impl Plugin for UiPlugin {
             screens::UiScreensPlugin,
             ending_text::EndingTextPlugin,
             vignette::VignettePlugin,
+            keybindings::KeybindingsPlugin,
+            pause_menu::PauseMenuPlugin,
+            settings::SettingsPlugin,
+            gallery::GalleryPlugin,
+            codex::CodexPlugin,
         ));
     }
 }
@@ -0,0 +1,291 @@
+use bevy::{color::palettes::css::GRAY, input::InputSystem, prelude::*};
+use bevy_rapier2d::plugin::RapierConfiguration;
+
+use crate::{
+    locale::{Locale, LocalizedText},
+    player::{
+        input::PlayerInput,
+        input_map::{InputAction, InputDevices, InputMap},
+    },
+    run::RestartRun,
+    GameState, PauseState,
+};
+
+use super::{
+    codex::{codex_menu_closed, CodexMenu},
+    dialogue::log::{dialogue_log_closed, DialogueLogMenu},
+    gallery::gallery_closed,
+    keybindings::keybindings_menu_closed,
+    settings::{settings_menu_closed, SettingsMenu},
+};
+
+const BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.7);
+
+#[derive(Clone, Copy, PartialEq)]
+enum PauseMenuEntry {
+    Resume,
+    Settings,
+    Codex,
+    DialogueLog,
+    Restart,
+    QuitToTitle,
+}
+
+impl PauseMenuEntry {
+    fn label_key(&self) -> &'static str {
+        match self {
+            PauseMenuEntry::Resume => "menu.resume",
+            PauseMenuEntry::Settings => "menu.settings",
+            PauseMenuEntry::Codex => "menu.codex",
+            PauseMenuEntry::DialogueLog => "menu.dialogue_log",
+            PauseMenuEntry::Restart => "menu.restart",
+            PauseMenuEntry::QuitToTitle => "menu.quit_to_title",
+        }
+    }
+}
+
+#[derive(Component)]
+struct PauseMenu;
+#[derive(Component)]
+struct PauseMenuButton {
+    index: usize,
+}
+
+#[derive(Resource, Default)]
+struct PauseMenuSelection {
+    entries: Vec<PauseMenuEntry>,
+    selected: usize,
+}
+
+fn available_entries(game_state: &GameState) -> Vec<PauseMenuEntry> {
+    let mut entries = vec![
+        PauseMenuEntry::Resume,
+        PauseMenuEntry::Settings,
+        PauseMenuEntry::Codex,
+        PauseMenuEntry::DialogueLog,
+    ];
+    // There is nothing to restart while the intro is still playing.
+    if *game_state != GameState::Intro {
+        entries.push(PauseMenuEntry::Restart);
+    }
+    entries.push(PauseMenuEntry::QuitToTitle);
+    entries
+}
+
+fn text_style_entry(locale: &Res<Locale>) -> TextStyle {
+    TextStyle {
+        font: locale.font(),
+        font_size: 60.0,
+        color: GRAY.into(),
+    }
+}
+
+fn pause_game(player_input: Res<PlayerInput>, mut next_state: ResMut<NextState<PauseState>>) {
+    if player_input.escape {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+fn freeze_game(mut time: ResMut<Time<Virtual>>, mut rapier_config: ResMut<RapierConfiguration>) {
+    // Typewriter, tweens, animations and audio fades all run on virtual time.
+    time.pause();
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn unfreeze_game(mut time: ResMut<Time<Virtual>>, mut rapier_config: ResMut<RapierConfiguration>) {
+    time.unpause();
+    rapier_config.physics_pipeline_active = true;
+}
+
+fn spawn_pause_menu(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    game_state: Res<State<GameState>>,
+    mut selection: ResMut<PauseMenuSelection>,
+) {
+    *selection = PauseMenuSelection {
+        entries: available_entries(game_state.get()),
+        selected: 0,
+    };
+
+    let header = commands
+        .spawn((
+            TextBundle::from_section(
+                locale.text("menu.paused"),
+                TextStyle {
+                    font: locale.font(),
+                    font_size: 90.0,
+                    color: Color::WHITE,
+                },
+            ),
+            LocalizedText("menu.paused"),
+        ))
+        .id();
+
+    let mut buttons = vec![header];
+    for (index, entry) in selection.entries.iter().enumerate() {
+        let text = commands
+            .spawn((
+                TextBundle::from_section(locale.text(entry.label_key()), text_style_entry(&locale)),
+                LocalizedText(entry.label_key()),
+                Label,
+            ))
+            .id();
+        buttons.push(
+            commands
+                .spawn((PauseMenuButton { index }, ButtonBundle::default()))
+                .add_child(text)
+                .id(),
+        );
+    }
+
+    commands
+        .spawn((
+            PauseMenu,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(400),
+                ..default()
+            },
+        ))
+        .push_children(&buttons);
+}
+
+fn despawn_pause_menu(mut commands: Commands, q_pause_menus: Query<Entity, With<PauseMenu>>) {
+    for entity in &q_pause_menus {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_pause_menu(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut selection: ResMut<PauseMenuSelection>,
+    q_buttons: Query<(&Interaction, &PauseMenuButton), Changed<Interaction>>,
+) {
+    let len = selection.entries.len();
+    if len == 0 {
+        return;
+    }
+
+    for (interaction, button) in &q_buttons {
+        if *interaction != Interaction::None {
+            selection.selected = button.index;
+        }
+    }
+
+    if input_map.just_pressed(InputAction::DialogueUp, &devices) {
+        selection.selected = (selection.selected + len - 1) % len;
+    }
+    if input_map.just_pressed(InputAction::DialogueDown, &devices) {
+        selection.selected = (selection.selected + 1) % len;
+    }
+}
+
+fn confirm_pause_menu(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    selection: Res<PauseMenuSelection>,
+    mut settings_menu: ResMut<SettingsMenu>,
+    mut codex_menu: ResMut<CodexMenu>,
+    mut dialogue_log_menu: ResMut<DialogueLogMenu>,
+    q_buttons: Query<&Interaction, (Changed<Interaction>, With<PauseMenuButton>)>,
+    mut next_state: ResMut<NextState<PauseState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut ev_restart_run: EventWriter<RestartRun>,
+) {
+    if input_map.just_pressed(InputAction::Escape, &devices) {
+        next_state.set(PauseState::Running);
+        return;
+    }
+
+    let clicked = q_buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    let confirmed = input_map.just_pressed(InputAction::DialogueConfirm, &devices)
+        || input_map.just_pressed(InputAction::Select, &devices);
+    if !clicked && !confirmed {
+        return;
+    }
+
+    let entry = match selection.entries.get(selection.selected) {
+        Some(r) => r,
+        None => return,
+    };
+    match entry {
+        PauseMenuEntry::Resume => next_state.set(PauseState::Running),
+        PauseMenuEntry::Settings => settings_menu.open(),
+        PauseMenuEntry::Codex => codex_menu.open(),
+        PauseMenuEntry::DialogueLog => dialogue_log_menu.open(),
+        PauseMenuEntry::Restart => {
+            ev_restart_run.send(RestartRun);
+        }
+        PauseMenuEntry::QuitToTitle => next_game_state.set(GameState::Title),
+    }
+}
+
+fn highlight_pause_menu(
+    selection: Res<PauseMenuSelection>,
+    q_buttons: Query<(&PauseMenuButton, &Children)>,
+    mut q_texts: Query<&mut Text>,
+) {
+    for (button, children) in &q_buttons {
+        let color = if button.index == selection.selected {
+            Color::WHITE
+        } else {
+            GRAY.into()
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = q_texts.get_mut(*child) {
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+}
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PauseMenuSelection>()
+            .add_systems(OnEnter(PauseState::Paused), (freeze_game, spawn_pause_menu))
+            .add_systems(
+                OnExit(PauseState::Paused),
+                (unfreeze_game, despawn_pause_menu),
+            )
+            .add_systems(
+                Update,
+                pause_game
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(gallery_closed),
+            )
+            // The keybindings menu closes on escape in `Update`,
+            // running before it makes sure we don't also resume the game with that same press.
+            .add_systems(
+                PreUpdate,
+                (navigate_pause_menu, confirm_pause_menu)
+                    .chain()
+                    .after(InputSystem)
+                    .run_if(in_state(PauseState::Paused))
+                    .run_if(keybindings_menu_closed)
+                    .run_if(settings_menu_closed)
+                    .run_if(codex_menu_closed)
+                    .run_if(dialogue_log_closed),
+            )
+            .add_systems(
+                Update,
+                highlight_pause_menu.run_if(in_state(PauseState::Paused)),
+            );
+    }
+}
@@ -1,64 +1,137 @@
 use bevy::prelude::*;
 use bevy_tweening::{lens::*, *};
 
-use crate::GameState;
+use crate::{run::RunScoped, GameState};
 
 const FADE_OUT_DURATION: f32 = 3.0;
 const FADE_IN_DURATION: f32 = 2.0;
 const BLACK_VISIBLE: Color = Color::srgba(0.0, 0.0, 0.0, 1.0);
 const BLACK_TRANSPARENT: Color = Color::srgba(0.0, 0.0, 0.0, 0.0);
+const FADE_SCREEN_TWEEN_ID: u64 = 20;
 
-fn fade_out_black_screen(mut commands: Commands) {
+/// Send this to fade the screen to black (or back from black) over `duration` seconds.
+#[derive(Event)]
+pub struct FadeScreen {
+    pub to_black: bool,
+    pub duration: f32,
+}
+
+/// Sent once the fade of a `FadeScreen` is done.
+#[derive(Event)]
+pub struct ScreenFaded {
+    pub to_black: bool,
+}
+
+#[derive(Component)]
+struct FadeOverlay {
+    to_black: bool,
+}
+
+fn spawn_fade(commands: &mut Commands, start: Color, end: Color, duration: f32) -> Entity {
     let tween = Tween::new(
         EaseFunction::CubicIn,
-        std::time::Duration::from_secs_f32(FADE_OUT_DURATION),
-        UiBackgroundColorLens {
-            start: BLACK_VISIBLE,
-            end: BLACK_TRANSPARENT,
-        },
-    );
+        std::time::Duration::from_secs_f32(duration),
+        UiBackgroundColorLens { start, end },
+    )
+    .with_completed_event(FADE_SCREEN_TWEEN_ID);
 
-    commands.spawn((
-        Animator::new(tween),
-        ImageBundle {
-            style: Style {
-                width: Val::Vw(110.0),
-                height: Val::Vh(110.0),
+    commands
+        .spawn((
+            RunScoped,
+            Animator::new(tween),
+            ImageBundle {
+                style: Style {
+                    width: Val::Vw(110.0),
+                    height: Val::Vh(110.0),
+                    ..default()
+                },
+                background_color: start.into(),
                 ..default()
             },
-            ..default()
-        },
-    ));
+        ))
+        .id()
+}
+
+fn fade_out_black_screen(mut commands: Commands) {
+    let overlay = spawn_fade(
+        &mut commands,
+        BLACK_VISIBLE,
+        BLACK_TRANSPARENT,
+        FADE_OUT_DURATION,
+    );
+    commands
+        .entity(overlay)
+        .insert(FadeOverlay { to_black: false });
 }
 
 fn fade_in_black_screen(mut commands: Commands) {
-    let tween = Tween::new(
-        EaseFunction::CubicIn,
-        std::time::Duration::from_secs_f32(FADE_IN_DURATION),
-        UiBackgroundColorLens {
-            start: BLACK_TRANSPARENT,
-            end: BLACK_VISIBLE,
-        },
+    let overlay = spawn_fade(
+        &mut commands,
+        BLACK_TRANSPARENT,
+        BLACK_VISIBLE,
+        FADE_IN_DURATION,
     );
+    commands
+        .entity(overlay)
+        .insert(FadeOverlay { to_black: true });
+}
 
-    commands.spawn((
-        Animator::new(tween),
-        ImageBundle {
-            style: Style {
-                width: Val::Vw(110.0),
-                height: Val::Vh(110.0),
-                ..default()
-            },
-            ..default()
-        },
-    ));
+fn fade_screens(
+    mut commands: Commands,
+    q_overlays: Query<Entity, With<FadeOverlay>>,
+    mut ev_fade_screen: EventReader<FadeScreen>,
+) {
+    for ev in ev_fade_screen.read() {
+        // Only ever keep one overlay around, otherwise they would stack up.
+        for entity in &q_overlays {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        let (start, end) = if ev.to_black {
+            (BLACK_TRANSPARENT, BLACK_VISIBLE)
+        } else {
+            (BLACK_VISIBLE, BLACK_TRANSPARENT)
+        };
+        let overlay = spawn_fade(&mut commands, start, end, ev.duration);
+        commands.entity(overlay).insert(FadeOverlay {
+            to_black: ev.to_black,
+        });
+    }
+}
+
+fn finish_fades(
+    mut commands: Commands,
+    q_overlays: Query<&FadeOverlay>,
+    mut ev_tween_completed: EventReader<TweenCompleted>,
+    mut ev_screen_faded: EventWriter<ScreenFaded>,
+) {
+    for ev in ev_tween_completed.read() {
+        if ev.user_data != FADE_SCREEN_TWEEN_ID {
+            continue;
+        }
+        let overlay = match q_overlays.get(ev.entity) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+
+        // A black overlay has to stay until something fades it out again.
+        if !overlay.to_black {
+            commands.entity(ev.entity).despawn_recursive();
+        }
+        ev_screen_faded.send(ScreenFaded {
+            to_black: overlay.to_black,
+        });
+    }
 }
 
 pub struct ScreenFadePlugin;
 
 impl Plugin for ScreenFadePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(GameState::Intro), (fade_out_black_screen,))
-            .add_systems(OnEnter(GameState::Ending), (fade_in_black_screen,));
+        app.add_event::<FadeScreen>()
+            .add_event::<ScreenFaded>()
+            .add_systems(OnExit(GameState::Intro), (fade_out_black_screen,))
+            .add_systems(OnEnter(GameState::Ending), (fade_in_black_screen,))
+            .add_systems(Update, (fade_screens, finish_fades).chain());
     }
 }
@@ -1,5 +1,11 @@
 mod fade_screen;
 mod splash_screen;
+mod title_screen;
+
+#[cfg(test)]
+mod test;
+
+pub use fade_screen::{FadeScreen, ScreenFaded};
 
 use bevy::prelude::*;
 
@@ -10,6 +16,7 @@ impl Plugin for UiScreensPlugin {
         app.add_plugins((
             fade_screen::ScreenFadePlugin,
             splash_screen::SplashScreenPlugin,
+            title_screen::TitleScreenPlugin,
         ));
     }
 }
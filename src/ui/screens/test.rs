@@ -0,0 +1,49 @@
+use std::fs;
+
+use super::title_screen::{parse_credits, CreditsLine};
+
+const PATH_TO_CREDITS: &str = "CREDITS.md";
+
+#[test]
+fn validate_credits_markdown_is_stripped() {
+    let contents = fs::read_to_string(PATH_TO_CREDITS).expect("Can't read credits file");
+    let lines = parse_credits(&contents);
+
+    assert!(!lines.is_empty());
+    for line in &lines {
+        assert!(!line.text.is_empty(), "empty credits line");
+        assert!(
+            !line.text.starts_with('#'),
+            "heading not stripped: {}",
+            line.text
+        );
+        assert!(
+            !line.text.contains("]("),
+            "link not stripped: {}",
+            line.text
+        );
+    }
+}
+
+#[test]
+fn validate_credits_headings_and_links() {
+    let lines =
+        parse_credits("# Credits\n\n### Art\n\nby [@someone](https://example.com), thanks\n");
+    assert_eq!(
+        lines,
+        vec![
+            CreditsLine {
+                text: "Credits".to_string(),
+                heading: 1,
+            },
+            CreditsLine {
+                text: "Art".to_string(),
+                heading: 3,
+            },
+            CreditsLine {
+                text: "by @someone, thanks".to_string(),
+                heading: 0,
+            },
+        ]
+    );
+}
@@ -0,0 +1,478 @@
+use bevy::{color::palettes::css::GRAY, prelude::*};
+
+use crate::{
+    locale::{Locale, LocalizedText},
+    player::{
+        input::PlayerInput,
+        input_map::{InputAction, InputDevices, InputMap},
+    },
+    save::{delete_save, save_exists},
+    ui::{
+        codex_menu_closed, keybindings_menu_closed, settings_menu_closed, CodexMenu, SettingsMenu,
+    },
+    GameState,
+};
+
+const CREDITS: &str = include_str!("../../../CREDITS.md");
+const BACKGROUND_COLOR: Color = Color::srgb(0.0, 0.0, 0.0);
+const CREDITS_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.9);
+/// Pixels per second when scrolling through the credits with keys or buttons.
+const CREDITS_SCROLL_SPEED: f32 = 400.0;
+/// Pixels per mouse wheel notch.
+const CREDITS_WHEEL_STEP: f32 = 40.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum TitleMenuEntry {
+    NewGame,
+    Continue,
+    Settings,
+    Codex,
+    Credits,
+    #[cfg(not(target_arch = "wasm32"))]
+    Quit,
+}
+
+impl TitleMenuEntry {
+    fn label_key(&self) -> &'static str {
+        match self {
+            TitleMenuEntry::NewGame => "menu.new_game",
+            TitleMenuEntry::Continue => "menu.continue",
+            TitleMenuEntry::Settings => "menu.settings",
+            TitleMenuEntry::Codex => "menu.codex",
+            TitleMenuEntry::Credits => "menu.credits",
+            #[cfg(not(target_arch = "wasm32"))]
+            TitleMenuEntry::Quit => "menu.quit",
+        }
+    }
+}
+
+/// A single line of the credits, `heading` is the markdown heading level, `0` for normal text.
+#[derive(Debug, PartialEq)]
+pub struct CreditsLine {
+    pub text: String,
+    pub heading: usize,
+}
+
+#[derive(Component)]
+struct TitleMenu;
+#[derive(Component)]
+struct TitleMenuButton {
+    index: usize,
+}
+#[derive(Component)]
+struct CreditsScreen;
+#[derive(Component)]
+struct CreditsViewport;
+#[derive(Component, Default)]
+struct CreditsScroll(f32);
+
+#[derive(Resource, Default)]
+struct TitleMenuSelection {
+    entries: Vec<TitleMenuEntry>,
+    selected: usize,
+    credits_open: bool,
+}
+
+fn available_entries() -> Vec<TitleMenuEntry> {
+    let mut entries = vec![TitleMenuEntry::NewGame];
+    if save_exists() {
+        entries.push(TitleMenuEntry::Continue);
+    }
+    entries.push(TitleMenuEntry::Settings);
+    entries.push(TitleMenuEntry::Codex);
+    entries.push(TitleMenuEntry::Credits);
+    #[cfg(not(target_arch = "wasm32"))]
+    entries.push(TitleMenuEntry::Quit);
+    entries
+}
+
+/// Replace markdown links `[text](url)` with just their text.
+fn strip_links(line: &str) -> String {
+    let mut result = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('[') {
+        let (before, after) = rest.split_at(start);
+        let link = after
+            .find(']')
+            .filter(|middle| after[*middle..].starts_with("]("))
+            .and_then(|middle| {
+                after[middle..]
+                    .find(')')
+                    .map(|end| (&after[1..middle], middle + end + 1))
+            });
+        match link {
+            Some((text, end)) => {
+                result.push_str(before);
+                result.push_str(text);
+                rest = &after[end..];
+            }
+            None => {
+                result.push_str(before);
+                result.push('[');
+                rest = &after[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+pub fn parse_credits(markdown: &str) -> Vec<CreditsLine> {
+    markdown
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let heading = line.chars().take_while(|c| *c == '#').count();
+            CreditsLine {
+                text: strip_links(line[heading..].trim()),
+                heading,
+            }
+        })
+        .collect()
+}
+
+fn text_style_entry(locale: &Res<Locale>) -> TextStyle {
+    TextStyle {
+        font: locale.font(),
+        font_size: 60.0,
+        color: GRAY.into(),
+    }
+}
+
+fn text_style_credits(locale: &Res<Locale>, heading: usize) -> TextStyle {
+    let (font_size, color) = match heading {
+        0 => (30.0, GRAY.into()),
+        1 => (80.0, Color::WHITE),
+        2 => (55.0, Color::WHITE),
+        _ => (40.0, Color::srgb(0.85, 0.85, 0.85)),
+    };
+    TextStyle {
+        font: locale.font(),
+        font_size,
+        color,
+    }
+}
+
+fn spawn_title_menu(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    mut selection: ResMut<TitleMenuSelection>,
+) {
+    *selection = TitleMenuSelection {
+        entries: available_entries(),
+        selected: 0,
+        credits_open: false,
+    };
+
+    let title = commands
+        .spawn(
+            TextBundle::from_section(
+                "Tsumi",
+                TextStyle {
+                    font: locale.font(),
+                    font_size: 160.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                margin: UiRect::bottom(Val::Px(40.0)),
+                ..default()
+            }),
+        )
+        .id();
+
+    let mut children = vec![title];
+    for (index, entry) in selection.entries.iter().enumerate() {
+        let text = commands
+            .spawn((
+                TextBundle::from_section(locale.text(entry.label_key()), text_style_entry(&locale)),
+                LocalizedText(entry.label_key()),
+                Label,
+            ))
+            .id();
+        children.push(
+            commands
+                .spawn((TitleMenuButton { index }, ButtonBundle::default()))
+                .add_child(text)
+                .id(),
+        );
+    }
+
+    commands
+        .spawn((
+            TitleMenu,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(300),
+                ..default()
+            },
+        ))
+        .push_children(&children);
+}
+
+fn despawn_title_menu(
+    mut commands: Commands,
+    q_title_menus: Query<Entity, Or<(With<TitleMenu>, With<CreditsScreen>)>>,
+) {
+    for entity in &q_title_menus {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn spawn_credits(commands: &mut Commands, locale: &Res<Locale>) {
+    let mut lines = Vec::new();
+    for line in parse_credits(CREDITS) {
+        let margin = if line.heading == 0 {
+            UiRect::default()
+        } else {
+            UiRect::top(Val::Px(20.0))
+        };
+        lines.push(
+            commands
+                .spawn(
+                    TextBundle::from_section(line.text, text_style_credits(locale, line.heading))
+                        .with_text_justify(JustifyText::Center)
+                        .with_style(Style {
+                            max_width: Val::Percent(80.0),
+                            margin,
+                            ..default()
+                        }),
+                )
+                .id(),
+        );
+    }
+
+    let scroll = commands
+        .spawn((
+            CreditsScroll::default(),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .push_children(&lines)
+        .id();
+
+    let viewport = commands
+        .spawn((
+            CreditsViewport,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    flex_grow: 1.0,
+                    flex_direction: FlexDirection::Column,
+                    overflow: Overflow::clip_y(),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .add_child(scroll)
+        .id();
+
+    let footer = commands
+        .spawn(TextBundle::from_section(
+            locale.text("credits.footer"),
+            TextStyle {
+                font: locale.font(),
+                font_size: 30.0,
+                color: GRAY.into(),
+            },
+        ))
+        .id();
+
+    commands
+        .spawn((
+            CreditsScreen,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::vertical(Val::Px(20.0)),
+                    ..default()
+                },
+                background_color: CREDITS_BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(350),
+                ..default()
+            },
+        ))
+        .add_child(viewport)
+        .add_child(footer);
+}
+
+fn navigate_title_menu(
+    player_input: Res<PlayerInput>,
+    mut selection: ResMut<TitleMenuSelection>,
+    q_buttons: Query<(&Interaction, &TitleMenuButton), Changed<Interaction>>,
+) {
+    let len = selection.entries.len();
+    if len == 0 {
+        return;
+    }
+
+    for (interaction, button) in &q_buttons {
+        if *interaction != Interaction::None {
+            selection.selected = button.index;
+        }
+    }
+
+    if player_input.dialogue_direction == 1 {
+        selection.selected = (selection.selected + len - 1) % len;
+    } else if player_input.dialogue_direction == -1 {
+        selection.selected = (selection.selected + 1) % len;
+    }
+}
+
+fn confirm_title_menu(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    player_input: Res<PlayerInput>,
+    mut selection: ResMut<TitleMenuSelection>,
+    mut settings_menu: ResMut<SettingsMenu>,
+    mut codex_menu: ResMut<CodexMenu>,
+    q_buttons: Query<&Interaction, (Changed<Interaction>, With<TitleMenuButton>)>,
+    q_credits_screens: Query<Entity, With<CreditsScreen>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    #[cfg(not(target_arch = "wasm32"))] mut ev_app_exit: EventWriter<AppExit>,
+) {
+    // Closing the credits is handled here too so that the same press can't reopen them.
+    if selection.credits_open {
+        if player_input.escape || player_input.dialogue_confirm {
+            for entity in &q_credits_screens {
+                commands.entity(entity).despawn_recursive();
+            }
+            selection.credits_open = false;
+        }
+        return;
+    }
+
+    let clicked = q_buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    let confirmed = player_input.dialogue_confirm || player_input.select_socket;
+    if !clicked && !confirmed {
+        return;
+    }
+
+    let entry = match selection.entries.get(selection.selected) {
+        Some(r) => *r,
+        None => return,
+    };
+    match entry {
+        TitleMenuEntry::NewGame => {
+            delete_save();
+            next_state.set(GameState::Intro);
+        }
+        TitleMenuEntry::Continue => next_state.set(GameState::Gaming),
+        TitleMenuEntry::Settings => settings_menu.open(),
+        TitleMenuEntry::Codex => codex_menu.open(),
+        TitleMenuEntry::Credits => {
+            spawn_credits(&mut commands, &locale);
+            selection.credits_open = true;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        TitleMenuEntry::Quit => {
+            ev_app_exit.send(AppExit::Success);
+        }
+    }
+}
+
+fn highlight_title_menu(
+    selection: Res<TitleMenuSelection>,
+    q_buttons: Query<(&TitleMenuButton, &Children)>,
+    mut q_texts: Query<&mut Text>,
+) {
+    for (button, children) in &q_buttons {
+        let color = if button.index == selection.selected {
+            Color::WHITE
+        } else {
+            GRAY.into()
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = q_texts.get_mut(*child) {
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+}
+
+fn scroll_credits(
+    time: Res<Time>,
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    player_input: Res<PlayerInput>,
+    q_viewport: Query<&Node, With<CreditsViewport>>,
+    mut q_scroll: Query<(&Node, &mut Style, &mut CreditsScroll)>,
+) {
+    let viewport = match q_viewport.get_single() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+    let (node, mut style, mut scroll) = match q_scroll.get_single_mut() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    let mut direction = -devices.left_stick().y;
+    if input_map.pressed(InputAction::DialogueUp, &devices) {
+        direction -= 1.0;
+    }
+    if input_map.pressed(InputAction::DialogueDown, &devices) {
+        direction += 1.0;
+    }
+
+    let max_scroll = (node.size().y - viewport.size().y).max(0.0);
+    scroll.0 += direction.clamp(-1.0, 1.0) * CREDITS_SCROLL_SPEED * time.delta_seconds()
+        + player_input.scroll * CREDITS_WHEEL_STEP;
+    scroll.0 = scroll.0.clamp(0.0, max_scroll);
+    style.top = Val::Px(-scroll.0);
+}
+
+fn credits_open(selection: Res<TitleMenuSelection>) -> bool {
+    selection.credits_open
+}
+
+pub struct TitleScreenPlugin;
+
+impl Plugin for TitleScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TitleMenuSelection>()
+            .add_systems(OnEnter(GameState::Title), spawn_title_menu)
+            .add_systems(OnExit(GameState::Title), despawn_title_menu)
+            .add_systems(
+                Update,
+                (
+                    navigate_title_menu.run_if(not(credits_open)),
+                    confirm_title_menu,
+                    scroll_credits.run_if(credits_open),
+                    highlight_title_menu,
+                )
+                    .chain()
+                    .run_if(keybindings_menu_closed)
+                    .run_if(settings_menu_closed)
+                    .run_if(codex_menu_closed)
+                    .run_if(in_state(GameState::Title)),
+            );
+    }
+}
@@ -0,0 +1,409 @@
+use bevy::{color::palettes::css::GRAY, input::InputSystem, prelude::*};
+use strum::IntoEnumIterator;
+
+use crate::{
+    audio::{AudioBus, GameAudio},
+    locale::{LanguageSetting, Locale, LocalizedText},
+    player::input_map::{InputAction, InputDevices, InputMap},
+    GameState,
+};
+
+use super::{
+    dialogue::advance::DialogueSettings,
+    keybindings::{keybindings_menu_closed, KeybindingsMenu},
+};
+
+const BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.85);
+const VOLUME_STEP: f64 = 0.1;
+
+#[derive(Clone, Copy, PartialEq)]
+enum SettingsRow {
+    MasterVolume,
+    BusVolume(AudioBus),
+    Language,
+    AutoAdvance,
+    SkipSeenLines,
+    Keybindings,
+    Back,
+}
+
+impl SettingsRow {
+    fn label_key(&self) -> &'static str {
+        match self {
+            SettingsRow::MasterVolume => "settings.master_volume",
+            SettingsRow::BusVolume(AudioBus::Music) => "settings.music",
+            SettingsRow::BusVolume(AudioBus::Sfx) => "settings.sfx",
+            SettingsRow::BusVolume(AudioBus::Blip) => "settings.blips",
+            SettingsRow::Language => "settings.language",
+            SettingsRow::AutoAdvance => "settings.auto_advance",
+            SettingsRow::SkipSeenLines => "settings.skip_seen_lines",
+            SettingsRow::Keybindings => "settings.keybindings",
+            SettingsRow::Back => "settings.back",
+        }
+    }
+
+    fn volume(&self, game_audio: &GameAudio) -> Option<f64> {
+        match self {
+            SettingsRow::MasterVolume => Some(game_audio.master_volume()),
+            SettingsRow::BusVolume(bus) => Some(game_audio.bus_volume(*bus)),
+            _ => None,
+        }
+    }
+
+    fn set_volume(&self, game_audio: &mut GameAudio, volume: f64) {
+        match self {
+            SettingsRow::MasterVolume => game_audio.set_master_volume(volume),
+            SettingsRow::BusVolume(bus) => game_audio.set_bus_volume(*bus, volume),
+            _ => {}
+        }
+    }
+
+    fn enabled(&self, dialogue_settings: &DialogueSettings) -> Option<bool> {
+        match self {
+            SettingsRow::AutoAdvance => Some(dialogue_settings.auto_advance),
+            SettingsRow::SkipSeenLines => Some(dialogue_settings.skip_seen_lines),
+            _ => None,
+        }
+    }
+
+    fn set_enabled(&self, dialogue_settings: &mut DialogueSettings, enabled: bool) {
+        match self {
+            SettingsRow::AutoAdvance => dialogue_settings.auto_advance = enabled,
+            SettingsRow::SkipSeenLines => dialogue_settings.skip_seen_lines = enabled,
+            _ => {}
+        }
+    }
+}
+
+fn rows() -> Vec<SettingsRow> {
+    let mut rows = vec![SettingsRow::MasterVolume];
+    rows.extend(AudioBus::iter().map(SettingsRow::BusVolume));
+    rows.push(SettingsRow::Language);
+    rows.push(SettingsRow::AutoAdvance);
+    rows.push(SettingsRow::SkipSeenLines);
+    rows.push(SettingsRow::Keybindings);
+    rows.push(SettingsRow::Back);
+    rows
+}
+
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    open: bool,
+    selected: usize,
+}
+
+impl SettingsMenu {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.selected = 0;
+    }
+}
+
+/// Menus below the settings only take input again once the settings screen is gone,
+/// so that the press that closed it doesn't also trigger something in them.
+pub fn settings_menu_closed(
+    menu: Res<SettingsMenu>,
+    q_screens: Query<(), With<SettingsScreen>>,
+) -> bool {
+    !menu.is_open() && q_screens.is_empty()
+}
+
+#[derive(Component)]
+pub struct SettingsScreen;
+
+#[derive(Component)]
+struct SettingsButton {
+    index: usize,
+}
+
+fn row_text(
+    row: &SettingsRow,
+    game_audio: &GameAudio,
+    dialogue_settings: &DialogueSettings,
+    locale: &Locale,
+) -> String {
+    let label = locale.text(row.label_key());
+    if *row == SettingsRow::Language {
+        return format!("{}:  < {} >", label, locale.language().native_name());
+    }
+    if let Some(enabled) = row.enabled(dialogue_settings) {
+        let value = if enabled {
+            "settings.on"
+        } else {
+            "settings.off"
+        };
+        return format!("{}:  < {} >", label, locale.text(value));
+    }
+    match row.volume(game_audio) {
+        Some(volume) => format!("{}:  < {:>3}% >", label, (volume * 100.0).round()),
+        None => label,
+    }
+}
+
+fn text_style_row(locale: &Res<Locale>) -> TextStyle {
+    TextStyle {
+        font: locale.font(),
+        font_size: 50.0,
+        color: GRAY.into(),
+    }
+}
+
+fn spawn_menu(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    game_audio: Res<GameAudio>,
+    dialogue_settings: Res<DialogueSettings>,
+) {
+    let header = commands
+        .spawn((
+            TextBundle::from_section(
+                locale.text("menu.settings"),
+                TextStyle {
+                    font: locale.font(),
+                    font_size: 80.0,
+                    color: Color::WHITE,
+                },
+            ),
+            LocalizedText("menu.settings"),
+        ))
+        .id();
+
+    let mut children = vec![header];
+    for (index, row) in rows().iter().enumerate() {
+        let text = commands
+            .spawn((
+                TextBundle::from_section(
+                    row_text(row, &game_audio, &dialogue_settings, &locale),
+                    text_style_row(&locale),
+                ),
+                Label,
+            ))
+            .id();
+        children.push(
+            commands
+                .spawn((SettingsButton { index }, ButtonBundle::default()))
+                .add_child(text)
+                .id(),
+        );
+    }
+
+    let footer = commands
+        .spawn((
+            TextBundle::from_section(
+                locale.text("settings.footer"),
+                TextStyle {
+                    font: locale.font(),
+                    font_size: 30.0,
+                    color: GRAY.into(),
+                },
+            ),
+            LocalizedText("settings.footer"),
+        ))
+        .id();
+    children.push(footer);
+
+    commands
+        .spawn((
+            SettingsScreen,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(450),
+                ..default()
+            },
+        ))
+        .push_children(&children);
+}
+
+fn despawn_menu(mut commands: Commands, q_screens: Query<Entity, With<SettingsScreen>>) {
+    for entity in &q_screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_menu(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    mut menu: ResMut<SettingsMenu>,
+    mut game_audio: ResMut<GameAudio>,
+    mut language_setting: ResMut<LanguageSetting>,
+    mut dialogue_settings: ResMut<DialogueSettings>,
+    mut keybindings_menu: ResMut<KeybindingsMenu>,
+    q_buttons: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+) {
+    if input_map.just_pressed(InputAction::Escape, &devices) {
+        menu.open = false;
+        return;
+    }
+
+    let rows = rows();
+    let len = rows.len();
+    let mut clicked = false;
+    for (interaction, button) in &q_buttons {
+        if *interaction != Interaction::None {
+            menu.selected = button.index;
+        }
+        clicked |= *interaction == Interaction::Pressed;
+    }
+
+    if input_map.just_pressed(InputAction::DialogueUp, &devices) {
+        menu.selected = (menu.selected + len - 1) % len;
+    }
+    if input_map.just_pressed(InputAction::DialogueDown, &devices) {
+        menu.selected = (menu.selected + 1) % len;
+    }
+
+    let row = match rows.get(menu.selected) {
+        Some(r) => *r,
+        None => return,
+    };
+
+    // Arrow keys always work here, in case left and right got rebound to something else.
+    let mut step = 0.0;
+    if input_map.just_pressed(InputAction::MoveLeft, &devices)
+        || devices.keys.just_pressed(KeyCode::ArrowLeft)
+    {
+        step -= VOLUME_STEP;
+    }
+    if input_map.just_pressed(InputAction::MoveRight, &devices)
+        || devices.keys.just_pressed(KeyCode::ArrowRight)
+    {
+        step += VOLUME_STEP;
+    }
+
+    let confirmed = clicked
+        || input_map.just_pressed(InputAction::DialogueConfirm, &devices)
+        || input_map.just_pressed(InputAction::Select, &devices);
+
+    if row == SettingsRow::Language {
+        // Confirming cycles forward, the same way clicking cycles through a volume.
+        let direction = if step != 0.0 {
+            step.signum() as i32
+        } else if confirmed {
+            1
+        } else {
+            0
+        };
+        if direction != 0 {
+            language_setting.language = language_setting.language.next(direction);
+            language_setting.save();
+        }
+        return;
+    }
+
+    if let Some(enabled) = row.enabled(&dialogue_settings) {
+        if confirmed || step != 0.0 {
+            row.set_enabled(&mut dialogue_settings, !enabled);
+            dialogue_settings.save();
+        }
+        return;
+    }
+
+    if let Some(volume) = row.volume(&game_audio) {
+        // Clicking on a volume cycles through it, mouse only users can still change it.
+        if clicked && step == 0.0 {
+            step = if volume >= 1.0 { -1.0 } else { VOLUME_STEP };
+        }
+        if step != 0.0 {
+            row.set_volume(&mut game_audio, volume + step);
+            game_audio.save();
+        }
+        return;
+    }
+
+    if !confirmed {
+        return;
+    }
+    match row {
+        SettingsRow::Keybindings => keybindings_menu.open(),
+        SettingsRow::Back => menu.open = false,
+        _ => {}
+    }
+}
+
+fn update_rows(
+    menu: Res<SettingsMenu>,
+    game_audio: Res<GameAudio>,
+    dialogue_settings: Res<DialogueSettings>,
+    locale: Res<Locale>,
+    q_buttons: Query<(&SettingsButton, &Children)>,
+    mut q_texts: Query<&mut Text>,
+) {
+    let rows = rows();
+    for (button, children) in &q_buttons {
+        let row = match rows.get(button.index) {
+            Some(r) => r,
+            None => continue,
+        };
+        let color = if button.index == menu.selected {
+            Color::WHITE
+        } else {
+            GRAY.into()
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = q_texts.get_mut(*child) {
+                text.sections[0].value = row_text(row, &game_audio, &dialogue_settings, &locale);
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+}
+
+/// The menu only reacts to input once it's on screen,
+/// so that the press that opened it doesn't also trigger a row.
+fn menu_shown(menu: Res<SettingsMenu>, q_screens: Query<(), With<SettingsScreen>>) -> bool {
+    menu.is_open() && !q_screens.is_empty()
+}
+
+fn menu_opened(menu: Res<SettingsMenu>, q_screens: Query<(), With<SettingsScreen>>) -> bool {
+    menu.is_open() && q_screens.is_empty()
+}
+
+fn menu_closed(menu: Res<SettingsMenu>, q_screens: Query<(), With<SettingsScreen>>) -> bool {
+    !menu.is_open() && !q_screens.is_empty()
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsMenu>()
+            // Same as the pause menu, this has to run before the keybindings menu
+            // closes on escape in `Update`, otherwise we would close both at once.
+            .add_systems(
+                PreUpdate,
+                navigate_menu
+                    .after(InputSystem)
+                    .run_if(menu_shown.and_then(keybindings_menu_closed)),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_menu.run_if(menu_opened),
+                    despawn_menu.run_if(menu_closed),
+                    update_rows.run_if(
+                        resource_changed::<SettingsMenu>
+                            .or_else(resource_changed::<GameAudio>)
+                            .or_else(resource_changed::<DialogueSettings>)
+                            .or_else(resource_changed::<Locale>),
+                    ),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
@@ -2,24 +2,28 @@ use bevy::prelude::*;
 
 use crate::{
     aspect::CombinedAspect,
+    run::RunScoped,
     world::{PlayerWentToBed, TriggerFirstImaDialogue},
     GameAssets, GameState,
 };
 
 fn spawn_vignette(mut commands: Commands, assets: Res<GameAssets>) {
-    commands.spawn(ImageBundle {
-        style: Style {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
+    commands.spawn((
+        RunScoped,
+        ImageBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            image: UiImage {
+                texture: assets.vignette.clone(),
+                color: Color::BLACK,
+                ..default()
+            },
             ..default()
         },
-        image: UiImage {
-            texture: assets.vignette.clone(),
-            color: Color::BLACK,
-            ..default()
-        },
-        ..default()
-    });
+    ));
 }
 
 pub struct VignettePlugin;
@@ -1,5 +1,7 @@
 mod debug;
 
+pub mod storage;
+
 pub use debug::DebugActive;
 
 use bevy::prelude::*;
@@ -0,0 +1,70 @@
+//! Persistent key-value storage.
+//! Files in the user data dir on native, local storage in the browser.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::PathBuf};
+
+#[cfg(not(target_arch = "wasm32"))]
+const APP_DIR: &str = "tsumi";
+#[cfg(target_arch = "wasm32")]
+const KEY_PREFIX: &str = "tsumi_";
+
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+fn path(name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR).join(name))
+}
+
+/// Tests get a directory of their own, so that they never touch the player's save or settings.
+#[cfg(all(not(target_arch = "wasm32"), test))]
+fn path(name: &str) -> Option<PathBuf> {
+    let dir = format!("{}-test-{}", APP_DIR, std::process::id());
+    Some(std::env::temp_dir().join(dir).join(name))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(name: &str) -> Option<String> {
+    fs::read_to_string(path(name)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(name: &str, contents: &str) -> Result<(), String> {
+    let path = path(name).ok_or("could not find the user data dir")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(name: &str) {
+    if let Some(path) = path(name) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(name: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("{KEY_PREFIX}{name}"))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(name: &str, contents: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or("local storage is not available")?
+        .set_item(&format!("{KEY_PREFIX}{name}"), contents)
+        .map_err(|err| format!("{:?}", err))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove(name: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(&format!("{KEY_PREFIX}{name}"));
+    }
+}
@@ -12,7 +12,8 @@ use bevy_rapier2d::plugin::PhysicsSet;
 use crate::player::input::PlayerInput;
 use crate::player::Player;
 use crate::utils::DebugActive;
-use shake::{update_camera, CameraShake, CameraShakePlugin};
+pub use shake::CameraShake;
+use shake::{update_camera, CameraShakePlugin};
 
 // Only relevant for the backend.
 // We have to multiply each z coordinate with this value
@@ -17,7 +17,6 @@ pub struct CameraShake {
 }
 
 impl CameraShake {
-    #[allow(dead_code)]
     pub fn add_trauma(&mut self, trauma: f32) {
         if self.trauma == 0.0 {
             self.seed = (Utc::now().timestamp_millis() & 0xFFFF) as f32;
@@ -6,7 +6,12 @@ use bevy_yarnspinner::events::DialogueCompleteEvent;
 use crate::{
     aspect::{AspectCombiner, Combiner},
     player::{input::PlayerInput, Player, PLAYER_PIVOT},
-    world::camera::YSort,
+    run::RunScoped,
+    save::GameLoaded,
+    world::{
+        camera::YSort,
+        map::level::{LevelEntered, LevelScoped},
+    },
     GameAssets, GameState,
 };
 
@@ -14,9 +19,11 @@ const PLAYER_HIGHLIGHT_DISTANCE: f32 = 32.0;
 const COMBINER_OFFSET: Vec3 = Vec3::new(128.0, 0.0, 0.0);
 
 #[derive(Component, Default)]
-pub struct Bed;
+pub struct Bed {
+    pub used: bool,
+}
 
-#[derive(Event)]
+#[derive(Event, Clone)]
 pub struct PlayerWentToBed;
 
 fn spawn_smoke_effect(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec3) {
@@ -24,6 +31,7 @@ fn spawn_smoke_effect(commands: &mut Commands, assets: &Res<GameAssets>, pos: Ve
     animator.play(assets.smoke_animations[0].clone());
 
     commands.spawn((
+        RunScoped,
         YSort(100.0),
         animator,
         SpriteBundle {
@@ -43,8 +51,9 @@ fn spawn_bed(
     assets: Res<GameAssets>,
     combiner: Res<Combiner>,
     q_combiner: Query<&Transform, With<AspectCombiner>>,
+    q_bed: Query<(), With<Bed>>,
 ) {
-    if !combiner.all_sockets_full {
+    if !combiner.all_sockets_full || !q_bed.is_empty() {
         return;
     }
 
@@ -57,7 +66,9 @@ fn spawn_bed(
     spawn_smoke_effect(&mut commands, &assets, pos);
 
     commands.spawn((
-        Bed,
+        RunScoped,
+        LevelScoped,
+        Bed::default(),
         YSort(0.0),
         Collider::cuboid(16.0, 16.0),
         SpriteBundle {
@@ -75,20 +86,19 @@ fn spawn_bed(
 fn highlight_and_select_bed(
     player_input: Res<PlayerInput>,
     q_player: Query<&Transform, With<Player>>,
-    mut q_bed: Query<(&Transform, &mut TextureAtlas), (With<Bed>, Without<Player>)>,
+    mut q_bed: Query<(&Transform, &mut TextureAtlas, &mut Bed), Without<Player>>,
     mut ev_player_went_to_bed: EventWriter<PlayerWentToBed>,
-    mut selected: Local<bool>,
 ) {
     let player_transform = match q_player.get_single() {
         Ok(r) => r,
         Err(_) => return,
     };
-    let (transform, mut atlas) = match q_bed.get_single_mut() {
+    let (transform, mut atlas, mut bed) = match q_bed.get_single_mut() {
         Ok(r) => r,
         Err(_) => return,
     };
 
-    let index = if !*selected
+    let index = if !bed.used
         && transform
             .translation
             .truncate()
@@ -96,7 +106,7 @@ fn highlight_and_select_bed(
             <= PLAYER_HIGHLIGHT_DISTANCE.powi(2)
     {
         if player_input.select_socket {
-            *selected = true;
+            bed.used = true;
             ev_player_went_to_bed.send(PlayerWentToBed);
         }
         1
@@ -113,7 +123,11 @@ impl Plugin for MapBedPlugin {
         app.add_systems(
             Update,
             (
-                spawn_bed.run_if(on_event::<DialogueCompleteEvent>()),
+                spawn_bed.run_if(
+                    on_event::<DialogueCompleteEvent>()
+                        .or_else(on_event::<GameLoaded>())
+                        .or_else(on_event::<LevelEntered>()),
+                ),
                 highlight_and_select_bed,
             )
                 .run_if(in_state(GameState::Gaming)),
@@ -2,6 +2,8 @@ use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+use crate::{run::RunScoped, world::map::level::LevelScoped};
+
 #[derive(Component, Default)]
 pub struct CollisionBox {
     width: f32,
@@ -23,8 +25,6 @@ struct CollisionBoxBundle {
     collision_box: CollisionBox,
     #[grid_coords]
     grid_coords: GridCoords,
-    #[worldly]
-    worldly: Worldly,
 }
 
 fn spawn_collision_boxes(
@@ -39,6 +39,8 @@ fn spawn_collision_boxes(
         );
 
         commands.spawn((
+            RunScoped,
+            LevelScoped,
             Collider::cuboid(collision_box.width, collision_box.height),
             TransformBundle::from_transform(Transform::from_translation(pos)),
         ));
@@ -3,7 +3,16 @@ use bevy_ecs_ldtk::GridCoords;
 use bevy_rapier2d::prelude::*;
 use bevy_trickfilm::prelude::*;
 
-use crate::{player::PlayerSpawnPos, world::camera::YSort, GameAssets, GameState};
+use crate::{
+    locale::Locale,
+    player::{
+        input_map::{Binding, InputAction, InputDevice, InputMap},
+        PlayerSpawnPos,
+    },
+    run::RunScoped,
+    world::{camera::YSort, map::level::LevelScoped},
+    GameAssets, GameState,
+};
 
 use super::tutorial::TutorialSwitchIntiater;
 
@@ -13,9 +22,25 @@ const BUTTON_DIS: f32 = 40.0;
 const ARROW_DIS: f32 = 80.0;
 const SHIFT_DIS: f32 = 60.0;
 const ICON_SIZE: f32 = 0.75;
+const KEY_LABEL_OFFSET: Vec3 = Vec3::new(-1.0, 3.0, 1.0);
+const BUTTON_LABEL_OFFSET: Vec3 = Vec3::new(0.0, 2.0, 1.0);
+const KEY_LABEL_SCALE: Vec3 = Vec3::splat(0.1);
+const KEY_LABEL_COLOR: Color = Color::srgb(0.25, 0.23, 0.58);
 
 #[derive(Component)]
 struct KeyboardIcon;
+/// A key icon that shows whatever key is currently bound to `action`.
+/// The baked in textures are used as long as the binding is the default one,
+/// otherwise we fall back to a blank key with the key name written on top.
+/// If the player is using a gamepad we show a blank button with the button name instead.
+#[derive(Component)]
+struct BoundKeyIcon {
+    action: InputAction,
+    texture: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+#[derive(Component)]
+struct KeyLabel;
 #[derive(Component)]
 pub struct KeyboardHint;
 
@@ -31,6 +56,19 @@ enum Icon {
     ShiftKey,
 }
 
+impl Icon {
+    fn action(&self) -> Option<InputAction> {
+        match self {
+            Icon::DownKey => Some(InputAction::MoveDown),
+            Icon::UpKey => Some(InputAction::MoveUp),
+            Icon::LeftKey => Some(InputAction::MoveLeft),
+            Icon::RightKey => Some(InputAction::MoveRight),
+            Icon::ShiftKey => Some(InputAction::Run),
+            _ => None,
+        }
+    }
+}
+
 fn icon_to_texture(
     assets: &Res<GameAssets>,
     icon: &Icon,
@@ -69,22 +107,60 @@ fn spawn_icon(
     let (texture, layout) = icon_to_texture(assets, &icon);
     let transform = Transform::from_translation(offset.extend(0.0));
 
-    let icon = commands
+    let entity = commands
         .spawn((
             KeyboardIcon,
             SpriteBundle {
-                texture,
+                texture: texture.clone(),
                 transform,
                 ..default()
             },
             TextureAtlas {
-                layout,
+                layout: layout.clone(),
+                ..default()
+            },
+        ))
+        .id();
+    if let Some(action) = icon.action() {
+        insert_bound_key(commands, entity, action, texture, layout);
+    }
+    commands.entity(root).push_children(&[entity]);
+    entity
+}
+
+/// The label gets its text and the font of the current language in `update_bound_key_icons`.
+fn insert_bound_key(
+    commands: &mut Commands,
+    entity: Entity,
+    action: InputAction,
+    texture: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+) {
+    let text_style = TextStyle {
+        font_size: 160.0,
+        color: KEY_LABEL_COLOR,
+        ..default()
+    };
+    let label = commands
+        .spawn((
+            KeyLabel,
+            Text2dBundle {
+                text: Text::from_section("", text_style).with_justify(JustifyText::Center),
+                transform: Transform::from_translation(KEY_LABEL_OFFSET)
+                    .with_scale(KEY_LABEL_SCALE),
                 ..default()
             },
         ))
         .id();
-    commands.entity(root).push_children(&[icon]);
-    icon
+
+    commands
+        .entity(entity)
+        .insert(BoundKeyIcon {
+            action,
+            texture,
+            layout,
+        })
+        .add_child(label);
 }
 
 fn spawn_animated_icon(
@@ -156,6 +232,8 @@ fn spawn_keyboard_ui(
         Transform::from_translation(pos + SPAWN_OFFSET).with_scale(Vec3::splat(ICON_SIZE));
     let root = commands
         .spawn((
+            RunScoped,
+            LevelScoped,
             KeyboardHint,
             YSort(-200.0),
             SpatialBundle::from_transform(transform),
@@ -247,19 +325,109 @@ fn spawn_selection_key_ui(
     animator.play(assets.ui_keys_animations[0].clone()).repeat();
 
     let transform = Transform::from_translation(pos + SELECTION_KEY_SPAWN_OFFSET);
-    commands.spawn((
-        animator,
-        YSort(-200.0),
-        SpriteBundle {
-            texture: assets.ui_interact_key_texture.clone(),
-            transform,
-            ..default()
-        },
-        TextureAtlas {
-            layout: assets.ui_interact_key_layout.clone(),
-            ..default()
-        },
-    ));
+    let entity = commands
+        .spawn((
+            RunScoped,
+            LevelScoped,
+            animator,
+            YSort(-200.0),
+            SpriteBundle {
+                texture: assets.ui_interact_key_texture.clone(),
+                transform,
+                ..default()
+            },
+            TextureAtlas {
+                layout: assets.ui_interact_key_layout.clone(),
+                ..default()
+            },
+        ))
+        .id();
+    insert_bound_key(
+        &mut commands,
+        entity,
+        InputAction::Select,
+        assets.ui_interact_key_texture.clone(),
+        assets.ui_interact_key_layout.clone(),
+    );
+}
+
+fn update_bound_key_icons(
+    assets: Res<GameAssets>,
+    locale: Res<Locale>,
+    input_map: Res<InputMap>,
+    input_device: Res<InputDevice>,
+    mut q_icons: Query<(
+        &BoundKeyIcon,
+        &Children,
+        &mut Handle<Image>,
+        &mut TextureAtlas,
+    )>,
+    mut q_labels: Query<(&mut Text, &mut Transform), With<KeyLabel>>,
+) {
+    for (icon, children, mut texture, mut atlas) in &mut q_icons {
+        let gamepad_button = match *input_device {
+            InputDevice::Gamepad => input_map.primary_gamepad_button(icon.action),
+            InputDevice::Keyboard => None,
+        };
+
+        let (new_texture, new_layout, label) = if let Some(button) = gamepad_button {
+            (
+                assets.ui_blank_button_texture.clone(),
+                assets.ui_blank_button_layout.clone(),
+                Binding::Gamepad(button).label(),
+            )
+        } else if input_map.is_default_primary_key(icon.action) {
+            (icon.texture.clone(), icon.layout.clone(), String::new())
+        } else {
+            let label = match input_map.primary_key(icon.action) {
+                Some(key) => Binding::Key(key).label(),
+                None => "?".to_string(),
+            };
+            (
+                assets.ui_blank_key_texture.clone(),
+                assets.ui_blank_key_layout.clone(),
+                label,
+            )
+        };
+
+        let label_offset = if gamepad_button.is_some() {
+            BUTTON_LABEL_OFFSET
+        } else {
+            KEY_LABEL_OFFSET
+        };
+
+        if *texture != new_texture {
+            *texture = new_texture;
+            atlas.layout = new_layout;
+            atlas.index = 0;
+        }
+
+        for child in children.iter() {
+            if let Ok((mut text, mut transform)) = q_labels.get_mut(*child) {
+                transform.translation = label_offset;
+                // Longer key names (Space, Enter, ...) wouldn't fit on the key otherwise.
+                text.sections[0].style.font_size = if label.chars().count() > 2 {
+                    80.0
+                } else {
+                    160.0
+                };
+                text.sections[0].style.font = locale.font();
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+fn bound_key_icons_need_update(
+    locale: Res<Locale>,
+    input_map: Res<InputMap>,
+    input_device: Res<InputDevice>,
+    q_icons: Query<(), Added<BoundKeyIcon>>,
+) -> bool {
+    locale.is_changed()
+        || input_map.is_changed()
+        || input_device.is_changed()
+        || !q_icons.is_empty()
 }
 
 pub struct KeyboardHintPlugin;
@@ -268,7 +436,12 @@ impl Plugin for KeyboardHintPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (spawn_keyboard_ui, spawn_selection_key_ui).run_if(in_state(GameState::Gaming)),
+            (
+                spawn_keyboard_ui,
+                spawn_selection_key_ui,
+                update_bound_key_icons.run_if(bound_key_icons_need_update),
+            )
+                .run_if(in_state(GameState::Gaming)),
         );
     }
 }
@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    player::{Player, PLAYER_PIVOT},
+    run::reset_resource,
+    ui::{FadeScreen, ScreenFaded},
+    GameState,
+};
+
+/// The level every new run starts in.
+pub const START_LEVEL: &str = "Level_0";
+const LEVEL_FADE_DURATION: f32 = 0.6;
+
+/// Marks entities that belong to the current level.
+/// They get despawned when the player moves on to another level.
+#[derive(Component)]
+pub struct LevelScoped;
+
+/// Sent once the player arrived in a new level and the screen starts to fade back in.
+#[derive(Event)]
+pub struct LevelEntered;
+
+/// The identifier of the level the player is in.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct CurrentLevel(pub String);
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        Self(START_LEVEL.to_string())
+    }
+}
+
+/// The exit the player walked into, we are waiting on the fade (or the new level) to finish.
+#[derive(Resource, Default)]
+struct LevelTransition {
+    target: Option<LevelExit>,
+    level_changed: bool,
+}
+
+#[derive(Component, Default, Clone)]
+pub struct LevelExit {
+    level: String,
+    entrance: String,
+    width: f32,
+    height: f32,
+}
+
+impl LevelExit {
+    /// A one tile exit as it would come out of the LDtk level, used to set up levels in tests.
+    #[cfg(test)]
+    pub fn new(level: &str, entrance: &str) -> Self {
+        Self {
+            level: level.to_string(),
+            entrance: entrance.to_string(),
+            width: 32.0,
+            height: 32.0,
+        }
+    }
+
+    fn from_field(entity_instance: &EntityInstance) -> Self {
+        let level = match entity_instance.get_string_field("level") {
+            Ok(r) => r.to_string(),
+            Err(err) => {
+                error!("level exit without a target level, {}", err);
+                String::new()
+            }
+        };
+        let entrance = match entity_instance.get_string_field("entrance") {
+            Ok(r) => r.to_string(),
+            Err(err) => {
+                error!("level exit without a target entrance, {}", err);
+                String::new()
+            }
+        };
+        Self {
+            level,
+            entrance,
+            width: entity_instance.width as f32,
+            height: entity_instance.height as f32,
+        }
+    }
+
+    /// Whether `pos` lies within the exit, `grid_coords` point to the top left tile of it.
+    fn contains(&self, grid_coords: &GridCoords, pos: Vec2) -> bool {
+        let max = Vec2::new(
+            grid_coords.x as f32 * 32.0 + self.width,
+            grid_coords.y as f32 * 32.0 + 32.0,
+        );
+        let min = Vec2::new(grid_coords.x as f32 * 32.0, max.y - self.height);
+        pos.cmpge(min).all() && pos.cmple(max).all()
+    }
+}
+
+#[derive(Component, Default)]
+pub struct LevelEntrance {
+    pub name: String,
+}
+
+impl LevelEntrance {
+    fn from_field(entity_instance: &EntityInstance) -> Self {
+        let name = match entity_instance.get_string_field("name") {
+            Ok(r) => r.to_string(),
+            Err(err) => {
+                error!("level entrance without a name, {}", err);
+                String::new()
+            }
+        };
+        Self { name }
+    }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+struct LevelExitBundle {
+    #[with(LevelExit::from_field)]
+    level_exit: LevelExit,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+struct LevelEntranceBundle {
+    #[with(LevelEntrance::from_field)]
+    level_entrance: LevelEntrance,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+fn select_start_level(mut level_selection: ResMut<LevelSelection>) {
+    *level_selection = LevelSelection::identifier(START_LEVEL);
+}
+
+fn enter_level_exits(
+    mut transition: ResMut<LevelTransition>,
+    mut q_player: Query<(&Transform, &mut Player)>,
+    q_level_exits: Query<(&GridCoords, &LevelExit)>,
+    mut ev_fade_screen: EventWriter<FadeScreen>,
+) {
+    if transition.target.is_some() {
+        return;
+    }
+    let (player_transform, mut player) = match q_player.get_single_mut() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+    if !player.can_move {
+        return;
+    }
+
+    let pos = player_transform.translation.truncate() + PLAYER_PIVOT;
+    let level_exit = match q_level_exits
+        .iter()
+        .find(|(grid_coords, level_exit)| level_exit.contains(grid_coords, pos))
+    {
+        Some((_, r)) => r,
+        None => return,
+    };
+
+    player.can_move = false;
+    *transition = LevelTransition {
+        target: Some(level_exit.clone()),
+        level_changed: false,
+    };
+    ev_fade_screen.send(FadeScreen {
+        to_black: true,
+        duration: LEVEL_FADE_DURATION,
+    });
+}
+
+/// The screen is black, swap out the level behind it.
+fn change_level(
+    mut commands: Commands,
+    mut transition: ResMut<LevelTransition>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut level_selection: ResMut<LevelSelection>,
+    q_level_scoped: Query<Entity, With<LevelScoped>>,
+    mut ev_screen_faded: EventReader<ScreenFaded>,
+) {
+    if !ev_screen_faded.read().any(|ev| ev.to_black) {
+        return;
+    }
+    let level = match &transition.target {
+        Some(r) => r.level.clone(),
+        None => return,
+    };
+
+    for entity in &q_level_scoped {
+        commands.entity(entity).despawn_recursive();
+    }
+    *level_selection = LevelSelection::identifier(&level);
+    current_level.0 = level;
+    transition.level_changed = true;
+}
+
+/// The new level is spawned, put the player at the entrance and fade back in.
+fn arrive_in_level(
+    mut transition: ResMut<LevelTransition>,
+    mut q_player: Query<(&mut Transform, &mut Player)>,
+    q_level_entrances: Query<(&GridCoords, &LevelEntrance)>,
+    mut ev_level: EventReader<LevelEvent>,
+    mut ev_fade_screen: EventWriter<FadeScreen>,
+    mut ev_level_entered: EventWriter<LevelEntered>,
+) {
+    if !ev_level
+        .read()
+        .any(|ev| matches!(ev, LevelEvent::Transformed(_)))
+    {
+        return;
+    }
+    if !transition.level_changed {
+        return;
+    }
+    let target = match transition.target.take() {
+        Some(r) => r,
+        None => return,
+    };
+    transition.level_changed = false;
+
+    let (mut player_transform, mut player) = match q_player.get_single_mut() {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    match q_level_entrances
+        .iter()
+        .find(|(_, entrance)| entrance.name == target.entrance)
+    {
+        Some((grid_coords, _)) => {
+            let pos = Vec2::new(grid_coords.x as f32 * 32.0, grid_coords.y as f32 * 32.0);
+            player_transform.translation = pos.extend(player_transform.translation.z);
+        }
+        None => error!(
+            "there is no entrance '{}' in level '{}'",
+            target.entrance, target.level
+        ),
+    }
+
+    player.can_move = true;
+    ev_fade_screen.send(FadeScreen {
+        to_black: false,
+        duration: LEVEL_FADE_DURATION,
+    });
+    ev_level_entered.send(LevelEntered);
+}
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<LevelExitBundle>("LevelExit")
+            .register_ldtk_entity::<LevelEntranceBundle>("LevelEntrance")
+            .insert_resource(LevelSelection::identifier(START_LEVEL))
+            .init_resource::<CurrentLevel>()
+            .init_resource::<LevelTransition>()
+            .add_event::<LevelEntered>()
+            .add_systems(
+                OnEnter(GameState::Intro),
+                (
+                    select_start_level,
+                    reset_resource::<CurrentLevel>,
+                    reset_resource::<LevelTransition>,
+                ),
+            )
+            .add_systems(OnEnter(GameState::Title), reset_resource::<LevelTransition>)
+            .add_systems(
+                Update,
+                (enter_level_exits, change_level, arrive_in_level)
+                    .chain()
+                    .run_if(in_state(GameState::Gaming)),
+            );
+    }
+}
@@ -1,15 +1,24 @@
 mod bed;
 mod collisions;
 mod keyboard_hint;
+mod level;
 mod tutorial;
 
-pub use bed::PlayerWentToBed;
-pub use tutorial::TriggerFirstImaDialogue;
+#[cfg(test)]
+mod test;
+
+#[cfg(test)]
+pub use bed::MapBedPlugin;
+pub use bed::{Bed, PlayerWentToBed};
+pub use level::{CurrentLevel, LevelEntered, LevelEntrance, LevelScoped};
+#[cfg(test)]
+pub use level::{LevelExit, LevelPlugin};
+pub use tutorial::{TriggerFirstImaDialogue, TutorialProgress};
 
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
-use crate::{GameAssets, GameState};
+use crate::{run::RunScoped, GameAssets, GameState};
 
 const Z_LEVEL_BACKGROUND: f32 = -999.0;
 
@@ -23,16 +32,19 @@ impl Plugin for MapPlugin {
                 tutorial::TutorialPlugin,
                 keyboard_hint::KeyboardHintPlugin,
                 bed::MapBedPlugin,
+                level::LevelPlugin,
             ))
-            .insert_resource(LevelSelection::index(0))
             .add_systems(OnEnter(GameState::Gaming), spawn_ldtk_world);
     }
 }
 
 fn spawn_ldtk_world(mut commands: Commands, assets: Res<GameAssets>) {
-    commands.spawn(LdtkWorldBundle {
-        ldtk_handle: assets.level.clone(),
-        transform: Transform::from_translation(Vec3::new(0.0, 0.0, Z_LEVEL_BACKGROUND)),
-        ..default()
-    });
+    commands.spawn((
+        RunScoped,
+        LdtkWorldBundle {
+            ldtk_handle: assets.level.clone(),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, Z_LEVEL_BACKGROUND)),
+            ..default()
+        },
+    ));
 }
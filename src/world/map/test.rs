@@ -0,0 +1,263 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    str::FromStr,
+};
+
+use bevy_ecs_ldtk::ldtk::{EntityInstance, LdtkJson};
+
+use crate::{
+    aspect::{Aspect, AspectRecipes},
+    harness::shipped_recipes,
+};
+
+const PATH_TO_LEVEL: &str = "assets/map/level.ldtk";
+
+/// Entities the player can spawn on, walk up to or interact with.
+const SPAWN_POINTS: [&str; 5] = [
+    "PlayerSpawnPos",
+    "LevelEntrance",
+    "AspectSocket",
+    "CombinerSocket",
+    "TutorialSwitch",
+];
+
+fn shipped_project() -> LdtkJson {
+    let contents =
+        fs::read_to_string(PATH_TO_LEVEL).expect("Should have been able to read the level");
+    serde_json::from_str(&contents).expect("The shipped LDtk project is invalid")
+}
+
+/// The entities of each level, by level identifier.
+fn shipped_levels() -> Vec<(String, Vec<EntityInstance>)> {
+    shipped_project()
+        .levels
+        .into_iter()
+        .map(|level| {
+            let entities = level
+                .layer_instances
+                .into_iter()
+                .flatten()
+                .flat_map(|layer| layer.entity_instances)
+                .collect();
+            (level.identifier, entities)
+        })
+        .collect()
+}
+
+/// The entities of all levels.
+fn shipped_entities() -> Vec<EntityInstance> {
+    shipped_levels()
+        .into_iter()
+        .flat_map(|(_, entities)| entities)
+        .collect()
+}
+
+/// The grid cells an entity covers, `grid` is its top left cell in LDtk.
+fn covered_cells(entity: &EntityInstance, grid_size: i32) -> (i32, i32, i32, i32) {
+    let width = (entity.width / grid_size).max(1);
+    let height = (entity.height / grid_size).max(1);
+    (
+        entity.grid.x,
+        entity.grid.y,
+        entity.grid.x + width,
+        entity.grid.y + height,
+    )
+}
+
+/// All aspects that have to be combined to get `aspect`, including itself.
+fn required_combinations(aspect: Aspect, recipes: &AspectRecipes) -> HashSet<Aspect> {
+    let mut required = HashSet::new();
+    // If there is more than one recipe for an aspect, the first one is enough.
+    if let Some(recipe) = recipes.recipes().iter().find(|r| r.result == aspect) {
+        required.insert(aspect);
+        required.extend(required_combinations(recipe.left, recipes));
+        required.extend(required_combinations(recipe.right, recipes));
+    }
+    required
+}
+
+#[test]
+fn validate_unique_entities() {
+    let entities = shipped_entities();
+    for identifier in [
+        "PlayerSpawnPos",
+        "CombinerSocket",
+        "TutorialSwitch",
+        "TutorialWall",
+    ] {
+        let count = entities
+            .iter()
+            .filter(|e| e.identifier == identifier)
+            .count();
+        assert_eq!(count, 1, "expected exactly one {identifier}, found {count}");
+    }
+}
+
+#[test]
+fn validate_socket_fields() {
+    for entity in shipped_entities()
+        .iter()
+        .filter(|e| e.identifier == "AspectSocket")
+    {
+        let aspect = entity
+            .get_enum_field("aspect")
+            .unwrap_or_else(|err| panic!("socket at {:?} has no aspect, {err}", entity.grid));
+        assert!(
+            Aspect::from_str(aspect).is_ok(),
+            "socket at {:?} has an unknown aspect '{aspect}'",
+            entity.grid
+        );
+        if let Err(err) = entity.get_bool_field("on_top") {
+            panic!("socket at {:?} has no on_top, {err}", entity.grid);
+        }
+    }
+}
+
+/// Only the combiner can fill sockets, so it has to be in the same level as the sockets.
+fn validate_level_sockets(level: &str, entities: &[EntityInstance], recipes: &AspectRecipes) {
+    let mut rows: HashMap<bool, Vec<Aspect>> = HashMap::new();
+    for entity in entities.iter().filter(|e| e.identifier == "AspectSocket") {
+        let aspect = Aspect::from_str(entity.get_enum_field("aspect").unwrap()).unwrap();
+        let on_top = *entity.get_bool_field("on_top").unwrap();
+        rows.entry(on_top).or_default().push(aspect);
+    }
+    assert!(
+        entities.iter().any(|e| e.identifier == "CombinerSocket"),
+        "{level} has sockets but no combiner"
+    );
+    for on_top in [true, false] {
+        assert!(
+            rows.contains_key(&on_top),
+            "{level} has no sockets with on_top: {on_top}"
+        );
+    }
+
+    for (on_top, row) in &rows {
+        let empty_sockets = row.iter().filter(|a| **a == Aspect::NotImplemented).count();
+        for recipe in recipes.recipes() {
+            let required = required_combinations(recipe.result, recipes);
+            assert!(
+                required.len() <= empty_sockets,
+                "{} takes {} combinations, but {} only has {} empty sockets (on_top: {})",
+                recipe.result,
+                required.len(),
+                level,
+                empty_sockets,
+                on_top
+            );
+        }
+    }
+
+    // Combined aspects go into both rows, base aspects have to be placed in the level.
+    let available = |aspect: Aspect, on_top: bool| {
+        rows.get(&on_top).is_some_and(|row| row.contains(&aspect))
+            || recipes.recipes().iter().any(|r| r.result == aspect)
+    };
+    for recipe in recipes.recipes() {
+        let as_is = available(recipe.left, true) && available(recipe.right, false);
+        let swapped = available(recipe.right, true) && available(recipe.left, false);
+        assert!(
+            as_is || (!recipe.ordered && swapped),
+            "{} has no sockets to combine {} and {} into {}",
+            level,
+            recipe.left,
+            recipe.right,
+            recipe.result
+        );
+    }
+}
+
+#[test]
+fn validate_enough_empty_sockets() {
+    let recipes = shipped_recipes();
+    let socket_levels = shipped_levels()
+        .into_iter()
+        .filter(|(_, entities)| entities.iter().any(|e| e.identifier == "AspectSocket"))
+        .collect::<Vec<_>>();
+    assert!(!socket_levels.is_empty(), "no level has any sockets");
+
+    for (level, entities) in &socket_levels {
+        validate_level_sockets(level, entities, &recipes);
+    }
+}
+
+#[test]
+fn validate_collisions_free_spawn_points() {
+    let grid_size = shipped_project().default_grid_size;
+
+    for (level, entities) in shipped_levels() {
+        let spawn_points = entities
+            .iter()
+            .filter(|e| SPAWN_POINTS.contains(&e.identifier.as_str()));
+        for spawn_point in spawn_points {
+            let (x, y, _, _) = covered_cells(spawn_point, grid_size);
+            for collision in entities.iter().filter(|e| e.identifier == "CollisionBox") {
+                let (left, top, right, bottom) = covered_cells(collision, grid_size);
+                assert!(
+                    !(left <= x && x < right && top <= y && y < bottom),
+                    "{} at {:?} in {} is inside the collision box at {:?}",
+                    spawn_point.identifier,
+                    spawn_point.grid,
+                    level,
+                    collision.grid
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn validate_level_exits_lead_to_entrances() {
+    let grid_size = shipped_project().default_grid_size;
+    let levels = shipped_levels();
+    let has_entrance = |level: &str, name: &str| {
+        levels
+            .iter()
+            .find(|(identifier, _)| identifier == level)
+            .and_then(|(_, entities)| {
+                entities.iter().find(|e| {
+                    e.identifier == "LevelEntrance"
+                        && e.get_string_field("name").is_ok_and(|n| n == name)
+                })
+            })
+            .is_some()
+    };
+
+    let mut exits = 0;
+    for (level, entities) in &levels {
+        for exit in entities.iter().filter(|e| e.identifier == "LevelExit") {
+            let target = exit.get_string_field("level").unwrap();
+            let name = exit.get_string_field("entrance").unwrap();
+            assert!(
+                has_entrance(target, name),
+                "the exit at {:?} in {} leads to '{}' in {}, which doesn't exist",
+                exit.grid,
+                level,
+                name,
+                target
+            );
+            exits += 1;
+        }
+
+        // Arriving inside of an exit would send the player right back.
+        for entrance in entities.iter().filter(|e| e.identifier == "LevelEntrance") {
+            let (x, y, _, _) = covered_cells(entrance, grid_size);
+            for exit in entities.iter().filter(|e| e.identifier == "LevelExit") {
+                let (left, top, right, bottom) = covered_cells(exit, grid_size);
+                assert!(
+                    !(left <= x && x < right && top <= y && y < bottom),
+                    "the entrance at {:?} in {} is inside the exit at {:?}",
+                    entrance.grid,
+                    level,
+                    exit.grid
+                );
+            }
+        }
+    }
+    assert!(
+        levels.len() == 1 || exits > 0,
+        "there are {} levels, but no exits between them",
+        levels.len()
+    );
+}
@@ -1,11 +1,14 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     audio::PlaySound,
     player::{input::PlayerInput, Player, PLAYER_PIVOT},
-    world::camera::YSort,
+    run::{reset_resource, RunScoped},
+    save::SaveGame,
+    world::{camera::YSort, map::level::LevelScoped},
     GameAssets, GameState,
 };
 
@@ -14,6 +17,12 @@ const PLAYER_HIGHLIGHT_DISTANCE: f32 = 32.0;
 #[derive(Event)]
 pub struct TriggerFirstImaDialogue;
 
+#[derive(Resource, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct TutorialProgress {
+    pub switch_triggered: bool,
+    pub met_ima: bool,
+}
+
 #[derive(Default, Component)]
 pub struct TutorialSwitchIntiater;
 
@@ -29,8 +38,6 @@ struct TutorialSwitchBundle {
     tutorial_switch_initiater: TutorialSwitchIntiater,
     #[grid_coords]
     grid_coords: GridCoords,
-    #[worldly]
-    worldly: Worldly,
 }
 
 #[derive(Default, Component)]
@@ -48,8 +55,6 @@ struct TutorialWallBundle {
     tutorial_wall_initiater: TutorialWallInitiater,
     #[grid_coords]
     grid_coords: GridCoords,
-    #[worldly]
-    worldly: Worldly,
 }
 
 #[derive(Component, Default)]
@@ -63,6 +68,7 @@ struct TutorialWall;
 fn spawn_tutorial_switch(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    tutorial_progress: Res<TutorialProgress>,
     q_tutorial_switch: Query<&GridCoords, Added<TutorialSwitchIntiater>>,
 ) {
     let grid_coords = match q_tutorial_switch.get_single() {
@@ -83,9 +89,17 @@ fn spawn_tutorial_switch(
         ))
         .id();
 
+    let (triggerd, index) = if tutorial_progress.switch_triggered {
+        (true, 2)
+    } else {
+        (false, 0)
+    };
+
     commands
         .spawn((
-            TutorialSwitch::default(),
+            RunScoped,
+            LevelScoped,
+            TutorialSwitch { triggerd },
             YSort(0.0),
             SpriteBundle {
                 texture: assets.tutorial_switch_texture.clone(),
@@ -94,7 +108,7 @@ fn spawn_tutorial_switch(
             },
             TextureAtlas {
                 layout: assets.tutorial_switch_layout.clone(),
-                ..default()
+                index,
             },
         ))
         .add_child(collider);
@@ -103,6 +117,7 @@ fn spawn_tutorial_switch(
 fn spawn_tutorial_wall(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    tutorial_progress: Res<TutorialProgress>,
     q_tutorial_wall: Query<&GridCoords, Added<TutorialWallInitiater>>,
 ) {
     let grid_coords = match q_tutorial_wall.get_single() {
@@ -110,6 +125,10 @@ fn spawn_tutorial_wall(
         Err(_) => return,
     };
 
+    if tutorial_progress.switch_triggered {
+        return;
+    }
+
     let pos = Vec3::new(
         grid_coords.x as f32 * 32.0 + 16.0,
         grid_coords.y as f32 * 32.0 + 16.0,
@@ -125,6 +144,8 @@ fn spawn_tutorial_wall(
 
     commands
         .spawn((
+            RunScoped,
+            LevelScoped,
             TutorialWall,
             SpriteBundle {
                 texture: assets.tutorial_wall.clone(),
@@ -170,9 +191,11 @@ fn trigger_tutorial_switch(
     mut commands: Commands,
     assets: Res<GameAssets>,
     player_input: Res<PlayerInput>,
+    mut tutorial_progress: ResMut<TutorialProgress>,
     mut q_tutorial_switch: Query<(&mut TextureAtlas, &mut TutorialSwitch)>,
     q_tutorial_wall: Query<Entity, With<TutorialWall>>,
     mut ev_play_sound: EventWriter<PlaySound>,
+    mut ev_save_game: EventWriter<SaveGame>,
 ) {
     if !player_input.select_socket {
         return;
@@ -185,6 +208,7 @@ fn trigger_tutorial_switch(
 
     if atlas.index == 1 {
         switch.triggerd = true;
+        tutorial_progress.switch_triggered = true;
         atlas.index = 2;
         for entity in &q_tutorial_wall {
             commands.entity(entity).despawn_recursive();
@@ -194,13 +218,19 @@ fn trigger_tutorial_switch(
             clip: assets.select_aspect.clone(),
             ..default()
         });
+        ev_save_game.send(SaveGame);
     }
 }
 
 fn set_player_x_value_trigger(
+    tutorial_progress: Res<TutorialProgress>,
     mut q_player: Query<&mut Player>,
     q_tutorial_wall: Query<&Transform, With<TutorialWall>>,
 ) {
+    if tutorial_progress.met_ima {
+        return;
+    }
+
     let mut player = match q_player.get_single_mut() {
         Ok(r) => r,
         Err(_) => return,
@@ -213,6 +243,7 @@ fn set_player_x_value_trigger(
 }
 
 fn trigger_first_dialogue(
+    mut tutorial_progress: ResMut<TutorialProgress>,
     mut q_player: Query<(&Transform, &mut Player)>,
     mut ev_trigger_first_dialogue: EventWriter<TriggerFirstImaDialogue>,
 ) {
@@ -223,6 +254,7 @@ fn trigger_first_dialogue(
 
     if transform.translation.x >= player.x_value_tutorial_dialogue {
         player.x_value_tutorial_dialogue = f32::MAX;
+        tutorial_progress.met_ima = true;
         ev_trigger_first_dialogue.send(TriggerFirstImaDialogue);
     }
 }
@@ -245,6 +277,11 @@ impl Plugin for TutorialPlugin {
                 )
                     .run_if(in_state(GameState::Gaming)),
             )
-            .add_event::<TriggerFirstImaDialogue>();
+            .init_resource::<TutorialProgress>()
+            .add_event::<TriggerFirstImaDialogue>()
+            .add_systems(
+                OnEnter(GameState::Intro),
+                reset_resource::<TutorialProgress>,
+            );
     }
 }
@@ -2,7 +2,12 @@ pub mod camera;
 
 mod map;
 
-pub use map::{PlayerWentToBed, TriggerFirstImaDialogue};
+pub use map::{
+    Bed, CurrentLevel, LevelEntered, LevelEntrance, LevelScoped, PlayerWentToBed,
+    TriggerFirstImaDialogue, TutorialProgress,
+};
+#[cfg(test)]
+pub use map::{LevelExit, LevelPlugin, MapBedPlugin};
 
 use bevy::prelude::*;
 use bevy_rapier2d::plugin::RapierConfiguration;